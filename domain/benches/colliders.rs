@@ -0,0 +1,90 @@
+//! Compare the grid broad phase of ColliderSystem with a full pairwise check.
+//!
+//! run with: cargo bench -p domain --bench colliders
+
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+use specs::prelude::*;
+
+use domain::components::{Collider, Owner, Position, Shape};
+use domain::models::{Contacts, V2};
+use domain::systems::ColliderSystem;
+
+const ITERATIONS: u32 = 20;
+
+fn new_world(count: usize) -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Collider>();
+    world.register::<Owner>();
+    world.insert(Contacts::default());
+
+    // keep the same density of objects for any count
+    let size = (count as f32).sqrt() * 40.0;
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..count {
+        world
+            .create_entity()
+            .with(Position {
+                pos: V2::new(rng.gen_range(0.0..size), rng.gen_range(0.0..size)),
+                angle: 0.0,
+            })
+            .with(Collider {
+                shape: Shape::Circle,
+                scale: rng.gen_range(2.0..15.0),
+                sensor: true,
+                ..Default::default()
+            })
+            .build();
+    }
+    world
+}
+
+fn bench_grid(world: &World) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut system = ColliderSystem {};
+        system.run_now(world);
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench_pairwise(world: &World) -> (Duration, usize) {
+    let positions = world.read_storage::<Position>();
+    let colliders = world.read_storage::<Collider>();
+    let bodies: Vec<_> = (&positions, &colliders).join().collect();
+
+    let start = Instant::now();
+    let mut contacts = 0;
+    for _ in 0..ITERATIONS {
+        contacts = 0;
+        for (i, (pos1, col1)) in bodies.iter().enumerate() {
+            for (pos2, col2) in &bodies[i + 1..] {
+                if pos1.pos.distance(pos2.pos) < col1.scale + col2.scale {
+                    contacts += 1;
+                }
+            }
+        }
+    }
+    (start.elapsed() / ITERATIONS, contacts)
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>10} {:>12} {:>12}",
+        "objects", "contacts", "grid", "pairwise"
+    );
+    for count in [100, 500, 1_000, 2_000, 5_000, 10_000] {
+        let world = new_world(count);
+        let grid = bench_grid(&world);
+        let contacts = world.read_resource::<Contacts>().list().len();
+        let (pairwise, pairwise_contacts) = bench_pairwise(&world);
+        assert_eq!(pairwise_contacts, contacts);
+
+        println!(
+            "{:>8} {:>10} {:>12?} {:>12?}",
+            count, contacts, grid, pairwise
+        );
+    }
+}
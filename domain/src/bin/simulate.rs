@@ -0,0 +1,135 @@
+//! Run sceneries without rendering and print the stats of each run.
+//!
+//! usage: simulate [--cfg <path>] [--brain <name>] [--seconds <n>] [--seeds <n>] [--first-seed <n>]
+
+use std::path::Path;
+use std::process::exit;
+
+use log::LevelFilter;
+
+use domain::brain::{new_brain, BRAIN_NAMES};
+use domain::cfg::Cfg;
+use domain::error::GameError;
+use domain::models::DeltaTime;
+use domain::simulation::{run_simulation, RunStats, SimulationParams};
+
+struct Args {
+    cfg: Option<String>,
+    brain: String,
+    seconds: f32,
+    seeds: u64,
+    first_seed: u64,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        cfg: None,
+        brain: "aim".to_string(),
+        seconds: 60.0,
+        seeds: 10,
+        first_seed: 0,
+    };
+
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--cfg" => args.cfg = Some(value()?),
+            "--brain" => args.brain = value()?,
+            "--seconds" => args.seconds = value()?.parse().map_err(|e| format!("{}", e))?,
+            "--seeds" => args.seeds = value()?.parse().map_err(|e| format!("{}", e))?,
+            "--first-seed" => args.first_seed = value()?.parse().map_err(|e| format!("{}", e))?,
+            other => return Err(format!("unknown argument {}", other)),
+        }
+    }
+
+    Ok(args)
+}
+
+fn print_stats(label: &str, stats: &RunStats) {
+    println!(
+        "{:>8} {:>10.1} {:>5} {:>6} {:>7} {:>6} {:>10.1} {:>10.1}",
+        label,
+        stats.time_survived.as_seconds_f64(),
+        if stats.died { "yes" } else { "no" },
+        stats.kills,
+        stats.score,
+        stats.level,
+        stats.mana_spent,
+        stats.damage_taken,
+    );
+}
+
+fn run(args: Args) -> Result<(), GameError> {
+    let cfg = match &args.cfg {
+        Some(path) => Cfg::load(Path::new(path))?,
+        None => Cfg::default(),
+    };
+
+    println!(
+        "{:>8} {:>10} {:>5} {:>6} {:>7} {:>6} {:>10} {:>10}",
+        "seed", "survived", "died", "kills", "score", "level", "mana", "damage"
+    );
+
+    let mut all = vec![];
+    for seed in args.first_seed..args.first_seed + args.seeds {
+        let params = SimulationParams {
+            cfg: cfg.clone(),
+            seed,
+            duration: DeltaTime(args.seconds),
+            ..Default::default()
+        };
+        let mut brain = new_brain(&args.brain, seed).ok_or(GameError::Msg(format!(
+            "unknown brain {}, expected one of {:?}",
+            args.brain, BRAIN_NAMES
+        )))?;
+        let stats = run_simulation(&params, brain.as_mut())?;
+        print_stats(&seed.to_string(), &stats);
+        all.push(stats);
+    }
+
+    if !all.is_empty() {
+        let n = all.len() as f32;
+        let avg = RunStats {
+            time_survived: (all
+                .iter()
+                .map(|s| s.time_survived.as_seconds_f64())
+                .sum::<f64>()
+                / n as f64)
+                .into(),
+            died: all.iter().all(|s| s.died),
+            kills: (all.iter().map(|s| s.kills as f32).sum::<f32>() / n).round() as u32,
+            score: (all.iter().map(|s| s.score as f32).sum::<f32>() / n).round() as i32,
+            level: (all.iter().map(|s| s.level as f32).sum::<f32>() / n).round() as i32,
+            mana_spent: all.iter().map(|s| s.mana_spent).sum::<f32>() / n,
+            damage_taken: all.iter().map(|s| s.damage_taken).sum::<f32>() / n,
+            ..Default::default()
+        };
+        print_stats("avg", &avg);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    _ = env_logger::builder()
+        .filter_level(LevelFilter::Warn)
+        .try_init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "usage: simulate [--cfg <path>] [--brain <{}>] [--seconds <n>] [--seeds <n>] [--first-seed <n>]",
+                BRAIN_NAMES.join("|")
+            );
+            exit(1);
+        }
+    };
+
+    if let Err(err) = run(args) {
+        eprintln!("simulation failed: {:?}", err);
+        exit(1);
+    }
+}
@@ -0,0 +1,142 @@
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+use specs::prelude::*;
+
+use crate::components::{Critter, Frame, Position, Team};
+use crate::models::*;
+use crate::player::{Player, PlayerInput};
+
+/// generate the player input from the world state, used to play without a human
+pub trait PlayerBrain {
+    fn next_input(&mut self, world: &World) -> PlayerInput;
+}
+
+/// names accepted by new_brain
+pub const BRAIN_NAMES: [&str; 4] = ["idle", "kite", "aim", "random"];
+
+pub fn new_brain(name: &str, seed: u64) -> Option<Box<dyn PlayerBrain>> {
+    let brain: Box<dyn PlayerBrain> = match name {
+        "idle" => Box::new(IdleBrain),
+        "kite" => Box::new(KiteBrain),
+        "aim" => Box::new(AimAndFireBrain),
+        "random" => Box::new(RandomWalkBrain::new(seed)),
+        _ => return None,
+    };
+    Some(brain)
+}
+
+/// do nothing
+pub struct IdleBrain;
+
+impl PlayerBrain for IdleBrain {
+    fn next_input(&mut self, _world: &World) -> PlayerInput {
+        PlayerInput::default()
+    }
+}
+
+/// stay still and keep casting the selected spell at the nearest enemy
+pub struct AimAndFireBrain;
+
+impl PlayerBrain for AimAndFireBrain {
+    fn next_input(&mut self, world: &World) -> PlayerInput {
+        match find_nearest_enemy(world) {
+            Some((_, target)) => PlayerInput {
+                mouse_pos: target,
+                cast_selected: true,
+                ..Default::default()
+            },
+            None => PlayerInput::default(),
+        }
+    }
+}
+
+/// run away from the nearest enemy while casting at it
+pub struct KiteBrain;
+
+impl PlayerBrain for KiteBrain {
+    fn next_input(&mut self, world: &World) -> PlayerInput {
+        let (player_pos, target) = match find_nearest_enemy(world) {
+            Some(value) => value,
+            None => return PlayerInput::default(),
+        };
+
+        let mut input_dir = (player_pos - target).normalize_or_zero();
+
+        // move along the border instead of leaving the screen
+        let screen_size = world.read_resource::<SceneryParams>().screen_size;
+        if (player_pos.x <= 0.0 && input_dir.x < 0.0)
+            || (player_pos.x >= screen_size.x && input_dir.x > 0.0)
+        {
+            input_dir.x = 0.0;
+        }
+        if (player_pos.y <= 0.0 && input_dir.y < 0.0)
+            || (player_pos.y >= screen_size.y && input_dir.y > 0.0)
+        {
+            input_dir.y = 0.0;
+        }
+
+        PlayerInput {
+            input_dir: input_dir.normalize_or_zero(),
+            mouse_pos: target,
+            cast_selected: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// walk in a random direction, changing it from time to time
+pub struct RandomWalkBrain {
+    rng: StdRng,
+    dir: V2,
+    next_change: TotalTime,
+}
+
+impl RandomWalkBrain {
+    /// seconds walking in the same direction
+    const CHANGE_INTERVAL: f32 = 1.0;
+
+    pub fn new(seed: u64) -> Self {
+        RandomWalkBrain {
+            rng: StdRng::seed_from_u64(seed),
+            dir: V2::ZERO,
+            next_change: TotalTime::default(),
+        }
+    }
+}
+
+impl PlayerBrain for RandomWalkBrain {
+    fn next_input(&mut self, world: &World) -> PlayerInput {
+        let total_time = world.read_resource::<Frame>().total_time;
+        if total_time.is_after(self.next_change) {
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            self.dir = V2::new(angle.cos(), angle.sin());
+            self.next_change = total_time.add(DeltaTime(Self::CHANGE_INTERVAL));
+        }
+
+        PlayerInput {
+            input_dir: self.dir,
+            ..Default::default()
+        }
+    }
+}
+
+/// return the player position and the position of the nearest enemy
+pub fn find_nearest_enemy(world: &World) -> Option<(V2, V2)> {
+    let positions = world.read_storage::<Position>();
+    let players = world.read_storage::<Player>();
+    let critters = world.read_storage::<Critter>();
+    let teams = world.read_storage::<Team>();
+
+    let player_pos = (&players, &positions).join().next()?.1.pos;
+
+    let nearest = (&positions, &critters, &teams)
+        .join()
+        .filter(|(_, _, team)| **team == Team::Enemy)
+        .map(|(pos, _, _)| pos.pos)
+        .min_by(|a, b| {
+            a.distance_squared(player_pos)
+                .total_cmp(&b.distance_squared(player_pos))
+        })?;
+
+    Some((player_pos, nearest))
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs_derive::Component;
 
@@ -5,7 +6,21 @@ use crate::spell::{Spell, SpellAtLevel, SpellBook, SpellBookEntry, SpellCode};
 
 use super::models::*;
 
-#[derive(Component, Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CastError {
+    NotEnoughMana,
+    /// already casting a spell
+    Busy,
+    /// recovering from the last cast
+    CalmDown,
+    UnknownSpell(SpellCode),
+    /// spell is in the book but has no definition for the current level
+    SpellLevelMissing(SpellCode),
+    /// the caster is stunned
+    Stunned,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Caster {
     pub mana: Mana,
     pub max_mana: Mana,
@@ -15,6 +30,10 @@ pub struct Caster {
     pub casting: CasterState,
     pub casting_skill: CastComplexity,
     pub spell_book: SpellBook,
+    /// point the ai is casting at, projectiles and aim areas go there instead of following the
+    /// caster angle, the player aims with the mouse
+    #[serde(default)]
+    pub target: Option<V2>,
 }
 
 impl Default for Caster {
@@ -26,12 +45,13 @@ impl Default for Caster {
             casting_skill: 1.0,
             casting: CasterState::Idle,
             spell_book: SpellBook::default(),
+            target: None,
         }
     }
 }
 
 impl Caster {
-    pub fn new(spells: &Vec<Spell>) -> Caster {
+    pub fn new(spells: &[Spell]) -> Caster {
         let mut caster = Caster::default();
         let spell_book = SpellBook {
             spells: spells
@@ -46,28 +66,37 @@ impl Caster {
         caster
     }
 
-    pub fn cast(&mut self, spell: SpellCode) -> Result<(), ()> {
-        let spell = self.get_spell(spell)?.clone();
-        self.cast_spell_at_level(spell)
+    /// start casting the spell, returns the mana deducted from the caster
+    pub fn cast(&mut self, code: SpellCode) -> Result<Mana, CastError> {
+        let spell = self.get_spell(code.clone())?.clone();
+        self.cast_spell_at_level(code, spell)
     }
 
-    fn cast_spell_at_level(&mut self, spell: SpellAtLevel) -> Result<(), ()> {
-        if !self.casting.is_idle() {
-            return Err(());
+    fn cast_spell_at_level(
+        &mut self,
+        code: SpellCode,
+        spell: SpellAtLevel,
+    ) -> Result<Mana, CastError> {
+        match self.casting {
+            CasterState::Idle => {}
+            CasterState::CalmDown { .. } => return Err(CastError::CalmDown),
+            _ => return Err(CastError::Busy),
         }
 
         if self.mana < spell.mana_cost {
-            return Err(());
+            return Err(CastError::NotEnoughMana);
         }
 
-        self.mana -= spell.mana_cost;
+        let mana = spell.mana_cost;
+        self.mana -= mana;
 
         self.casting = CasterState::Casting {
-            spell: spell.clone(),
+            code,
             progress: spell.cast_complexity,
+            spell,
         };
 
-        Ok(())
+        Ok(mana)
     }
 
     pub fn update(&mut self, delta_time: DeltaTime) {
@@ -84,15 +113,21 @@ impl Caster {
         let cast_skill = self.casting_skill * delta_time.as_seconds_f32();
         match &mut self.casting {
             CasterState::Idle => {}
-            CasterState::Casting { spell, progress } => {
+            CasterState::Casting {
+                code,
+                spell,
+                progress,
+            } => {
                 *progress -= cast_skill;
                 log::trace!("casting progress {:.2}", progress);
                 if *progress <= 0.0 {
-                    let spell = spell.clone();
-                    self.casting = CasterState::Cast { spell };
+                    self.casting = CasterState::Cast {
+                        code: code.clone(),
+                        spell: spell.clone(),
+                    };
                 }
             }
-            CasterState::Cast { spell } => {
+            CasterState::Cast { spell, .. } => {
                 log::debug!(
                     "casted complete, starting calm down {:.2}",
                     spell.calm_down_complexity
@@ -111,20 +146,33 @@ impl Caster {
         }
     }
 
-    pub fn has_cast(&self) -> Option<&SpellAtLevel> {
+    pub fn has_cast(&self) -> Option<(&SpellCode, &SpellAtLevel)> {
         match &self.casting {
-            CasterState::Cast { spell } => Some(spell),
+            CasterState::Cast { code, spell } => Some((code, spell)),
             _ => None,
         }
     }
 
-    pub fn get_spell(&self, code: SpellCode) -> Result<&SpellAtLevel, ()> {
+    /// there is not enough mana to cast any spell of the book
+    pub fn is_mana_depleted(&self) -> bool {
         self.spell_book
             .spells
             .iter()
-            .find(|e| e.spell.spell_code == code)
-            .ok_or(())
-            .and_then(|e| e.spell.per_level.get(e.level as usize).ok_or(()))
+            .filter_map(|e| e.current())
+            .all(|spell| spell.mana_cost > self.mana)
+    }
+
+    pub fn get_spell(&self, code: SpellCode) -> Result<&SpellAtLevel, CastError> {
+        let entry = self
+            .spell_book
+            .find(&code)
+            .ok_or_else(|| CastError::UnknownSpell(code.clone()))?;
+        entry.current().ok_or(CastError::SpellLevelMissing(code))
+    }
+
+    /// check if the spell could be cast now
+    pub fn can_cast(&self, spell: &SpellAtLevel) -> bool {
+        self.casting.is_idle() && self.mana >= spell.mana_cost
     }
 }
 
@@ -135,7 +183,7 @@ mod test {
 
     use super::*;
 
-    const SPELL_CODE: &'static str = "spell";
+    const SPELL_CODE: &str = "spell";
 
     const SPELL: SpellAtLevel = SpellAtLevel {
         mana_cost: 5.0,
@@ -145,6 +193,7 @@ mod test {
             damage: 1.0,
             speed: 1.0,
             ttl: DeltaTime(1.0),
+            status: None,
         },
     };
 
@@ -178,8 +227,41 @@ mod test {
         let mut c = new_caster();
         let not_enough_mana = SPELL.mana_cost - 1.0;
         c.mana = not_enough_mana;
-        assert!(c.cast(SpellCode::from(SPELL_CODE)).is_err());
+        assert_eq!(
+            Err(CastError::NotEnoughMana),
+            c.cast(SpellCode::from(SPELL_CODE))
+        );
         assert!(c.casting.is_idle());
         assert_eq!(not_enough_mana, c.mana);
     }
+
+    #[test]
+    fn test_caster_busy_and_calm_down() {
+        let mut c = new_caster();
+        assert!(c.cast(SpellCode::from(SPELL_CODE)).is_ok());
+        assert_eq!(Err(CastError::Busy), c.cast(SpellCode::from(SPELL_CODE)));
+
+        c.casting = CasterState::CalmDown { progress: 1.0 };
+        assert_eq!(
+            Err(CastError::CalmDown),
+            c.cast(SpellCode::from(SPELL_CODE))
+        );
+    }
+
+    #[test]
+    fn test_caster_invalid_spell() {
+        let mut c = new_caster();
+        let unknown = SpellCode::from("unknown");
+        assert_eq!(
+            Err(CastError::UnknownSpell(unknown.clone())),
+            c.cast(unknown)
+        );
+
+        c.spell_book.spells[0].level = 5;
+        assert_eq!(
+            Err(CastError::SpellLevelMissing(SpellCode::from(SPELL_CODE))),
+            c.cast(SpellCode::from(SPELL_CODE))
+        );
+        assert_eq!(c.max_mana, c.mana);
+    }
 }
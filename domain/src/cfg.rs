@@ -1,18 +1,247 @@
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 
-use crate::models::DeltaTime;
-use crate::spell::{Spell, SpellAtLevel, SpellCode, SpellEffect};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::components::Shape;
+use crate::error::GameError;
+use crate::models::*;
+use crate::spell::{AreaTarget, Spell, SpellAtLevel, SpellCode, SpellEffect};
+use crate::status::{StatusEffectCfg, StatusKind};
+
+pub type EnemyCode = Arc<str>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Cfg {
     pub spells: Vec<Spell>,
+    pub player: PlayerCfg,
+    pub enemies: Vec<EnemyCfg>,
+    pub spawn: SpawnCfg,
+    /// static obstacles placed when the scenery starts
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleCfg>,
+}
+
+/// base stats of the player when a scenery starts
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerCfg {
+    pub model: Model,
+    pub hp: Hp,
+    pub speed: Speed,
+    pub collider_radius: Radius,
+    pub mass: Mass,
+    pub max_mana: Mana,
+    pub mana_recharge: Mana,
+    pub casting_skill: CastComplexity,
+    pub skill_points: SkillPoint,
+}
+
+/// enemy archetype
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnemyCfg {
+    pub code: EnemyCode,
+    pub model: Model,
+    pub hp: Hp,
+    pub speed: Speed,
+    pub collider_radius: Radius,
+    pub mass: Mass,
+    /// damage applied on contact
+    pub damage: Damage,
+    pub kill_score: Score,
+    #[serde(default)]
+    pub ai: AiCfg,
+    /// enemies with a caster cast their spells at the player when it is in reach
+    #[serde(default)]
+    pub caster: Option<EnemyCasterCfg>,
+    /// applied to the player on contact damage
+    #[serde(default)]
+    pub status: Option<StatusEffectCfg>,
+    #[serde(default)]
+    pub boss: Option<BossCfg>,
+}
+
+/// scripted enemy that changes its behaviour as it loses hp
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BossCfg {
+    /// granted to the killer on top of the kill score
+    pub defeat_score: Score,
+    /// sorted by hp threshold from higher to lower
+    pub phases: Vec<BossPhaseCfg>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BossPhaseCfg {
+    /// the phase starts when hp / max hp falls to this fraction, use 1.0 for the first one
+    pub hp_threshold: f32,
+    /// replaces the ai of the boss, the previous one is kept when none
+    #[serde(default)]
+    pub ai: Option<AiCfg>,
+    /// applied to the speed of the enemy cfg
+    pub speed_multiplier: f32,
+    /// repeated on every interval while the phase is active
+    #[serde(default)]
+    pub attack: Option<BossAttackCfg>,
+    #[serde(default)]
+    pub attack_interval: DeltaTime,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum BossAttackCfg {
+    /// spawn enemies around the boss
+    Summon { enemy: EnemyCode, count: u32 },
+    /// projectiles evenly spread in all directions
+    RadialBurst {
+        projectiles: u32,
+        damage: Damage,
+        speed: Speed,
+        ttl: DeltaTime,
+    },
+}
+
+/// spell book of an enemy, the first level of every spell is used
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnemyCasterCfg {
+    pub spells: Vec<Spell>,
+    pub max_mana: Mana,
+    pub mana_recharge: Mana,
+    pub casting_skill: CastComplexity,
+}
+
+/// behaviour of an enemy archetype
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum AiCfg {
+    /// walk to the player around obstacles and hit on contact
+    #[default]
+    Melee,
+    Ranged(RangedAiCfg),
+    Charger(ChargerAiCfg),
+    Swarmer(SwarmerAiCfg),
+}
+
+/// keep the distance to the player, attacks come from the caster of the enemy
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RangedAiCfg {
+    pub preferred_distance: f32,
+}
+
+/// approach the player, stop to wind up and dash straight at it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChargerAiCfg {
+    /// start winding up when the player is closer than it
+    pub charge_distance: f32,
+    pub wind_up: DeltaTime,
+    pub dash_speed: Speed,
+    pub dash_duration: DeltaTime,
+    /// stand still after the dash
+    pub recover: DeltaTime,
+}
+
+/// follow the player flocking with the swarmers around, weights are relative to the chase
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SwarmerAiCfg {
+    pub neighbour_radius: Radius,
+    pub separation: f32,
+    pub cohesion: f32,
+    pub alignment: f32,
+}
+
+/// waves of enemies, after the last wave they repeat from the first with more enemies
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnCfg {
+    pub waves: Vec<WaveCfg>,
+    /// pause between a cleared wave and the next one
+    pub break_time: DeltaTime,
+    /// spawn intervals are divided by 1 + time_scaling * minutes + level_scaling * player level
+    pub time_scaling: f32,
+    pub level_scaling: f32,
+    pub min_interval: DeltaTime,
+    /// enemy counts grow by this fraction every time the waves repeat
+    pub repeat_scaling: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WaveCfg {
+    /// spawned one by one in random order
+    pub enemies: Vec<WaveEntry>,
+    /// time between spawns before the difficulty scaling
+    pub interval: DeltaTime,
+    /// spawned when the wave starts, makes it a boss wave
+    #[serde(default)]
+    pub boss: Option<EnemyCode>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WaveEntry {
+    pub enemy: EnemyCode,
+    pub count: u32,
+}
+
+impl SpawnCfg {
+    /// wave by index starting on zero, waves after the last repeat with scaled counts
+    pub fn get_wave(&self, index: u32) -> Option<WaveCfg> {
+        if self.waves.is_empty() {
+            return None;
+        }
+
+        let len = self.waves.len() as u32;
+        let mut wave = self.waves[(index % len) as usize].clone();
+        let scale = 1.0 + self.repeat_scaling * (index / len) as f32;
+        for entry in &mut wave.enemies {
+            entry.count = (entry.count as f32 * scale).round() as u32;
+        }
+        Some(wave)
+    }
+
+    /// time between spawns of the wave, shorter as the game goes on
+    pub fn get_interval(&self, wave: &WaveCfg, total_time: TotalTime, level: Level) -> DeltaTime {
+        let minutes = total_time.as_seconds_f64() as f32 / 60.0;
+        let divisor = 1.0 + self.time_scaling * minutes + self.level_scaling * level as f32;
+        let interval = wave.interval.as_seconds_f32() / divisor.max(1.0);
+        DeltaTime(interval.max(self.min_interval.as_seconds_f32()))
+    }
+}
+
+/// static obstacle, positions are in screen coordinates
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObstacleCfg {
+    pub model: Model,
+    pub pos: V2,
+    pub angle: Radians,
+    pub shape: Shape,
+    pub scale: f32,
 }
 
+pub const MODEL_PLAYER: &str = "player";
 pub const MODEL_MAGIC_MISSILE: &str = "magic_missile";
+pub const MODEL_FIREBALL: &str = "fireball";
+pub const MODEL_AREA: &str = "area";
 pub const MODEL_ENEMY_1: &str = "enemy_1";
+pub const MODEL_ENEMY_RANGED: &str = "enemy_ranged";
+pub const MODEL_ENEMY_CHARGER: &str = "enemy_charger";
+pub const MODEL_ENEMY_SWARMER: &str = "enemy_swarmer";
+pub const MODEL_ENEMY_TANK: &str = "enemy_tank";
+pub const MODEL_BOSS_1: &str = "boss_1";
 
 impl Default for Cfg {
     fn default() -> Self {
+        let burn = |duration: f32, damage_per_second: Damage| {
+            Some(StatusEffectCfg {
+                kind: StatusKind::Burn,
+                duration: DeltaTime(duration),
+                damage_per_second,
+                slow: 0.0,
+            })
+        };
+        let freeze = |duration: f32| {
+            Some(StatusEffectCfg {
+                kind: StatusKind::Freeze,
+                duration: DeltaTime(duration),
+                damage_per_second: 0.0,
+                slow: 0.0,
+            })
+        };
+
         let firebold = Spell {
             spell_code: Arc::from("firebold"),
             per_level: vec![
@@ -22,6 +251,7 @@ impl Default for Cfg {
                         damage: 10.0,
                         speed: 500.0,
                         ttl: DeltaTime(5.0),
+                        status: None,
                     },
                     cast_complexity: 0.5,
                     calm_down_complexity: 0.1,
@@ -32,6 +262,7 @@ impl Default for Cfg {
                         damage: 20.0,
                         speed: 500.0,
                         ttl: DeltaTime(5.0),
+                        status: None,
                     },
                     cast_complexity: 0.5,
                     calm_down_complexity: 0.1,
@@ -42,6 +273,7 @@ impl Default for Cfg {
                         damage: 20.0,
                         speed: 500.0,
                         ttl: DeltaTime(5.0),
+                        status: None,
                     },
                     cast_complexity: 0.5,
                     calm_down_complexity: 0.1,
@@ -49,8 +281,349 @@ impl Default for Cfg {
             ],
         };
 
+        let fireball = Spell {
+            spell_code: Arc::from("fireball"),
+            per_level: vec![
+                SpellAtLevel {
+                    mana_cost: 5.0,
+                    effect: SpellEffect::ExplosiveProject {
+                        damage: 10.0,
+                        speed: 300.0,
+                        radius: 50.0,
+                        ttl: DeltaTime(2.0),
+                        status: burn(3.0, 2.0),
+                    },
+                    cast_complexity: 1.0,
+                    calm_down_complexity: 0.5,
+                },
+                SpellAtLevel {
+                    mana_cost: 5.0,
+                    effect: SpellEffect::ExplosiveProject {
+                        damage: 15.0,
+                        speed: 300.0,
+                        radius: 60.0,
+                        ttl: DeltaTime(2.0),
+                        status: burn(3.0, 3.0),
+                    },
+                    cast_complexity: 1.0,
+                    calm_down_complexity: 0.5,
+                },
+                SpellAtLevel {
+                    mana_cost: 4.0,
+                    effect: SpellEffect::ExplosiveProject {
+                        damage: 20.0,
+                        speed: 300.0,
+                        radius: 75.0,
+                        ttl: DeltaTime(2.0),
+                        status: burn(3.0, 4.0),
+                    },
+                    cast_complexity: 1.0,
+                    calm_down_complexity: 0.5,
+                },
+            ],
+        };
+
+        let frost_nova = Spell {
+            spell_code: Arc::from("frost_nova"),
+            per_level: vec![
+                SpellAtLevel {
+                    mana_cost: 6.0,
+                    effect: SpellEffect::Area {
+                        damage: 5.0,
+                        radius: 80.0,
+                        target: AreaTarget::Caster,
+                        duration: DeltaTime(0.0),
+                        status: freeze(1.0),
+                    },
+                    cast_complexity: 0.5,
+                    calm_down_complexity: 1.0,
+                },
+                SpellAtLevel {
+                    mana_cost: 6.0,
+                    effect: SpellEffect::Area {
+                        damage: 8.0,
+                        radius: 100.0,
+                        target: AreaTarget::Caster,
+                        duration: DeltaTime(0.0),
+                        status: freeze(1.5),
+                    },
+                    cast_complexity: 0.5,
+                    calm_down_complexity: 1.0,
+                },
+                SpellAtLevel {
+                    mana_cost: 5.0,
+                    effect: SpellEffect::Area {
+                        damage: 12.0,
+                        radius: 120.0,
+                        target: AreaTarget::Caster,
+                        duration: DeltaTime(0.0),
+                        status: freeze(2.0),
+                    },
+                    cast_complexity: 0.5,
+                    calm_down_complexity: 1.0,
+                },
+            ],
+        };
+
+        let fire_circle = Spell {
+            spell_code: Arc::from("fire_circle"),
+            per_level: vec![
+                SpellAtLevel {
+                    mana_cost: 8.0,
+                    effect: SpellEffect::Area {
+                        damage: 5.0,
+                        radius: 60.0,
+                        target: AreaTarget::Aim,
+                        duration: DeltaTime(3.0),
+                        status: None,
+                    },
+                    cast_complexity: 1.5,
+                    calm_down_complexity: 1.0,
+                },
+                SpellAtLevel {
+                    mana_cost: 8.0,
+                    effect: SpellEffect::Area {
+                        damage: 8.0,
+                        radius: 60.0,
+                        target: AreaTarget::Aim,
+                        duration: DeltaTime(4.0),
+                        status: None,
+                    },
+                    cast_complexity: 1.5,
+                    calm_down_complexity: 1.0,
+                },
+                SpellAtLevel {
+                    mana_cost: 7.0,
+                    effect: SpellEffect::Area {
+                        damage: 10.0,
+                        radius: 80.0,
+                        target: AreaTarget::Aim,
+                        duration: DeltaTime(5.0),
+                        status: None,
+                    },
+                    cast_complexity: 1.5,
+                    calm_down_complexity: 1.0,
+                },
+            ],
+        };
+
+        let player = PlayerCfg {
+            model: Arc::from(MODEL_PLAYER),
+            hp: 100.0,
+            speed: 100.0,
+            collider_radius: 12.0,
+            mass: 1.0,
+            max_mana: 10.0,
+            mana_recharge: 1.0,
+            casting_skill: 1.0,
+            skill_points: 4,
+        };
+
+        let enemy_1 = EnemyCfg {
+            code: Arc::from("enemy_1"),
+            model: Arc::from(MODEL_ENEMY_1),
+            hp: 10.0,
+            speed: 50.0,
+            collider_radius: 12.0,
+            mass: 1.0,
+            damage: 1.0,
+            kill_score: 1,
+            ai: AiCfg::Melee,
+            caster: None,
+            status: None,
+            boss: None,
+        };
+
+        let ranged = EnemyCfg {
+            code: Arc::from("ranged"),
+            model: Arc::from(MODEL_ENEMY_RANGED),
+            hp: 8.0,
+            speed: 40.0,
+            collider_radius: 12.0,
+            mass: 1.0,
+            damage: 0.5,
+            kill_score: 2,
+            ai: AiCfg::Ranged(RangedAiCfg {
+                preferred_distance: 250.0,
+            }),
+            caster: Some(EnemyCasterCfg {
+                spells: vec![Spell {
+                    spell_code: Arc::from("enemy_bolt"),
+                    per_level: vec![SpellAtLevel {
+                        mana_cost: 1.0,
+                        effect: SpellEffect::Projectile {
+                            damage: 3.0,
+                            speed: 200.0,
+                            ttl: DeltaTime(3.0),
+                            status: Some(StatusEffectCfg {
+                                kind: StatusKind::Slow,
+                                duration: DeltaTime(2.0),
+                                damage_per_second: 0.0,
+                                slow: 0.4,
+                            }),
+                        },
+                        cast_complexity: 0.5,
+                        calm_down_complexity: 2.0,
+                    }],
+                }],
+                max_mana: 3.0,
+                mana_recharge: 0.5,
+                casting_skill: 1.0,
+            }),
+            status: None,
+            boss: None,
+        };
+
+        let charger = EnemyCfg {
+            code: Arc::from("charger"),
+            model: Arc::from(MODEL_ENEMY_CHARGER),
+            hp: 15.0,
+            speed: 40.0,
+            collider_radius: 14.0,
+            mass: 2.0,
+            damage: 3.0,
+            kill_score: 3,
+            ai: AiCfg::Charger(ChargerAiCfg {
+                charge_distance: 200.0,
+                wind_up: DeltaTime(0.8),
+                dash_speed: 300.0,
+                dash_duration: DeltaTime(0.6),
+                recover: DeltaTime(1.0),
+            }),
+            caster: None,
+            status: None,
+            boss: None,
+        };
+
+        let swarmer = EnemyCfg {
+            code: Arc::from("swarmer"),
+            model: Arc::from(MODEL_ENEMY_SWARMER),
+            hp: 4.0,
+            speed: 70.0,
+            collider_radius: 8.0,
+            mass: 0.5,
+            damage: 0.5,
+            kill_score: 1,
+            ai: AiCfg::Swarmer(SwarmerAiCfg {
+                neighbour_radius: 60.0,
+                separation: 1.5,
+                cohesion: 0.5,
+                alignment: 0.5,
+            }),
+            caster: None,
+            status: Some(StatusEffectCfg {
+                kind: StatusKind::Poison,
+                duration: DeltaTime(2.0),
+                damage_per_second: 1.0,
+                slow: 0.0,
+            }),
+            boss: None,
+        };
+
+        let tank = EnemyCfg {
+            code: Arc::from("tank"),
+            model: Arc::from(MODEL_ENEMY_TANK),
+            hp: 80.0,
+            speed: 25.0,
+            collider_radius: 20.0,
+            mass: 5.0,
+            damage: 5.0,
+            kill_score: 8,
+            ai: AiCfg::Melee,
+            caster: None,
+            status: None,
+            boss: None,
+        };
+
+        let boss_1 = EnemyCfg {
+            code: Arc::from("boss_1"),
+            model: Arc::from(MODEL_BOSS_1),
+            hp: 300.0,
+            speed: 30.0,
+            collider_radius: 28.0,
+            mass: 10.0,
+            damage: 8.0,
+            kill_score: 20,
+            ai: AiCfg::Melee,
+            caster: None,
+            status: None,
+            boss: Some(BossCfg {
+                defeat_score: 100,
+                phases: vec![
+                    BossPhaseCfg {
+                        hp_threshold: 1.0,
+                        ai: None,
+                        speed_multiplier: 1.0,
+                        attack: Some(BossAttackCfg::Summon {
+                            enemy: swarmer.code.clone(),
+                            count: 3,
+                        }),
+                        attack_interval: DeltaTime(6.0),
+                    },
+                    BossPhaseCfg {
+                        hp_threshold: 0.6,
+                        ai: Some(AiCfg::Ranged(RangedAiCfg {
+                            preferred_distance: 250.0,
+                        })),
+                        speed_multiplier: 1.0,
+                        attack: Some(BossAttackCfg::RadialBurst {
+                            projectiles: 12,
+                            damage: 4.0,
+                            speed: 150.0,
+                            ttl: DeltaTime(4.0),
+                        }),
+                        attack_interval: DeltaTime(3.0),
+                    },
+                    // enrage
+                    BossPhaseCfg {
+                        hp_threshold: 0.25,
+                        ai: Some(AiCfg::Melee),
+                        speed_multiplier: 2.5,
+                        attack: Some(BossAttackCfg::RadialBurst {
+                            projectiles: 16,
+                            damage: 4.0,
+                            speed: 200.0,
+                            ttl: DeltaTime(4.0),
+                        }),
+                        attack_interval: DeltaTime(1.5),
+                    },
+                ],
+            }),
+        };
+
+        let wave = |interval: f32, boss: Option<&EnemyCfg>, enemies: &[(&EnemyCfg, u32)]| WaveCfg {
+            enemies: enemies
+                .iter()
+                .map(|(enemy, count)| WaveEntry {
+                    enemy: enemy.code.clone(),
+                    count: *count,
+                })
+                .collect(),
+            interval: DeltaTime(interval),
+            boss: boss.map(|enemy| enemy.code.clone()),
+        };
+
+        let spawn = SpawnCfg {
+            waves: vec![
+                wave(1.5, None, &[(&enemy_1, 5)]),
+                wave(1.0, None, &[(&enemy_1, 6), (&swarmer, 6)]),
+                wave(1.2, None, &[(&enemy_1, 4), (&ranged, 3), (&charger, 2)]),
+                wave(0.8, None, &[(&swarmer, 10), (&charger, 3), (&tank, 1)]),
+                wave(1.0, Some(&boss_1), &[(&enemy_1, 6), (&ranged, 4)]),
+            ],
+            break_time: DeltaTime(5.0),
+            time_scaling: 0.1,
+            level_scaling: 0.05,
+            min_interval: DeltaTime(0.3),
+            repeat_scaling: 0.5,
+        };
+
         Cfg {
-            spells: vec![firebold],
+            spells: vec![firebold, fireball, frost_nova, fire_circle],
+            player,
+            enemies: vec![enemy_1, ranged, charger, swarmer, tank, boss_1],
+            spawn,
+            obstacles: vec![],
         }
     }
 }
@@ -59,4 +632,422 @@ impl Cfg {
     pub fn find_spell(&self, code: SpellCode) -> Option<&Spell> {
         self.spells.iter().find(|s| s.spell_code == code)
     }
+
+    pub fn find_enemy(&self, code: &str) -> Option<&EnemyCfg> {
+        self.enemies.iter().find(|e| e.code.as_ref() == code)
+    }
+
+    /// load and validate a cfg file, the format is decided by the extension, `.ron` or `.json`
+    pub fn load(path: &Path) -> Result<Cfg, GameError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| GameError::Msg(format!("fail to read {:?}: {}", path, e)))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Cfg::from_ron(&content),
+            Some("json") => Cfg::from_json(&content),
+            _ => Err(GameError::InvalidCfg(format!(
+                "unsupported cfg format {:?}",
+                path
+            ))),
+        }
+    }
+
+    pub fn from_ron(content: &str) -> Result<Cfg, GameError> {
+        let cfg: Cfg = ron::from_str(content)
+            .map_err(|e| GameError::InvalidCfg(format!("fail to parse ron: {}", e)))?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    pub fn from_json(content: &str) -> Result<Cfg, GameError> {
+        let cfg: Cfg = serde_json::from_str(content)
+            .map_err(|e| GameError::InvalidCfg(format!("fail to parse json: {}", e)))?;
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    pub fn to_ron(&self) -> Result<String, GameError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| GameError::Msg(format!("fail to serialize cfg: {}", e)))
+    }
+
+    pub fn validate(&self) -> Result<(), GameError> {
+        validate_spells(&self.spells)?;
+
+        if self.player.hp <= 0.0 || self.player.collider_radius <= 0.0 || self.player.mass <= 0.0 {
+            return invalid("player has invalid hp, collider radius or mass".to_string());
+        }
+
+        if self.player.casting_skill <= 0.0 {
+            return invalid("player casting skill must be positive".to_string());
+        }
+
+        let mut enemy_codes = HashSet::new();
+        for enemy in &self.enemies {
+            if !enemy_codes.insert(enemy.code.clone()) {
+                return invalid(format!("duplicated enemy {}", enemy.code));
+            }
+
+            if enemy.hp <= 0.0 || enemy.collider_radius <= 0.0 || enemy.mass <= 0.0 {
+                return invalid(format!(
+                    "enemy {} has invalid hp, collider radius or mass",
+                    enemy.code
+                ));
+            }
+
+            if !enemy.status.as_ref().is_none_or(is_valid_status) {
+                return invalid(format!("enemy {} has invalid status", enemy.code));
+            }
+
+            if !is_valid_ai(&enemy.ai) {
+                return invalid(format!("enemy {} has invalid ai", enemy.code));
+            }
+
+            if let Some(caster) = &enemy.caster {
+                validate_spells(&caster.spells)?;
+                if caster.casting_skill <= 0.0 {
+                    return invalid(format!(
+                        "enemy {} casting skill must be positive",
+                        enemy.code
+                    ));
+                }
+            }
+        }
+
+        // after all enemies, bosses can summon any of them
+        for (enemy, boss) in self
+            .enemies
+            .iter()
+            .filter_map(|enemy| enemy.boss.as_ref().map(|boss| (enemy, boss)))
+        {
+            if boss.phases.is_empty() {
+                return invalid(format!("boss {} has no phases", enemy.code));
+            }
+
+            let mut previous_threshold = f32::INFINITY;
+            for phase in &boss.phases {
+                if phase.hp_threshold <= 0.0
+                    || phase.hp_threshold > 1.0
+                    || phase.hp_threshold >= previous_threshold
+                {
+                    return invalid(format!(
+                        "boss {} phase thresholds must be descending in (0, 1]",
+                        enemy.code
+                    ));
+                }
+                previous_threshold = phase.hp_threshold;
+
+                if phase.speed_multiplier < 0.0 || !phase.ai.as_ref().is_none_or(is_valid_ai) {
+                    return invalid(format!("boss {} has an invalid phase", enemy.code));
+                }
+
+                let valid_attack = match &phase.attack {
+                    None => true,
+                    Some(BossAttackCfg::Summon { enemy: code, .. }) => enemy_codes.contains(code),
+                    Some(BossAttackCfg::RadialBurst {
+                        projectiles, speed, ..
+                    }) => *projectiles > 0 && *speed > 0.0,
+                };
+                if !valid_attack
+                    || (phase.attack.is_some() && phase.attack_interval.as_seconds_f32() <= 0.0)
+                {
+                    return invalid(format!("boss {} has an invalid attack", enemy.code));
+                }
+            }
+        }
+
+        if self.spawn.waves.is_empty() {
+            return invalid("spawn has no waves".to_string());
+        }
+
+        if self.spawn.min_interval.as_seconds_f32() <= 0.0
+            || self.spawn.break_time.as_seconds_f32() < 0.0
+        {
+            return invalid("spawn has invalid min interval or break time".to_string());
+        }
+
+        for (index, wave) in self.spawn.waves.iter().enumerate() {
+            if wave.interval.as_seconds_f32() <= 0.0 {
+                return invalid(format!("wave {} interval must be positive", index));
+            }
+
+            for code in wave
+                .enemies
+                .iter()
+                .map(|e| &e.enemy)
+                .chain(wave.boss.iter())
+            {
+                if !enemy_codes.contains(code) {
+                    return invalid(format!("wave {} has unknown enemy {}", index, code));
+                }
+            }
+
+            if wave.boss.is_none() && wave.enemies.iter().map(|e| e.count).sum::<u32>() == 0 {
+                return invalid(format!("wave {} has no enemies", index));
+            }
+        }
+
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            let valid_shape = match &obstacle.shape {
+                Shape::Circle => true,
+                Shape::Aabb { half_extents } | Shape::Rect { half_extents } => {
+                    half_extents.x > 0.0 && half_extents.y > 0.0
+                }
+                Shape::Capsule {
+                    half_length,
+                    radius,
+                } => *half_length >= 0.0 && *radius > 0.0,
+            };
+
+            if obstacle.scale <= 0.0 || !valid_shape {
+                return invalid(format!("obstacle {} has invalid shape", index));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid<T>(msg: String) -> Result<T, GameError> {
+    Err(GameError::InvalidCfg(msg))
+}
+
+fn is_valid_ai(ai: &AiCfg) -> bool {
+    match ai {
+        AiCfg::Melee => true,
+        AiCfg::Ranged(ranged) => ranged.preferred_distance > 0.0,
+        AiCfg::Charger(charger) => {
+            charger.charge_distance > 0.0
+                && charger.dash_speed > 0.0
+                && charger.dash_duration.as_seconds_f32() > 0.0
+        }
+        AiCfg::Swarmer(swarmer) => swarmer.neighbour_radius > 0.0,
+    }
+}
+
+fn is_valid_status(status: &StatusEffectCfg) -> bool {
+    status.duration.as_seconds_f32() > 0.0
+        && status.damage_per_second >= 0.0
+        && (0.0..=1.0).contains(&status.slow)
+}
+
+fn validate_spells(spells: &[Spell]) -> Result<(), GameError> {
+    let mut spell_codes = HashSet::new();
+    for spell in spells {
+        if !spell_codes.insert(spell.spell_code.clone()) {
+            return invalid(format!("duplicated spell {}", spell.spell_code));
+        }
+
+        if spell.per_level.is_empty() {
+            return invalid(format!("spell {} has no levels", spell.spell_code));
+        }
+
+        for (level, at_level) in spell.per_level.iter().enumerate() {
+            if at_level.mana_cost < 0.0
+                || at_level.cast_complexity < 0.0
+                || at_level.calm_down_complexity < 0.0
+            {
+                return invalid(format!(
+                    "spell {} level {} has negative cost",
+                    spell.spell_code, level
+                ));
+            }
+
+            let radius = match &at_level.effect {
+                SpellEffect::Projectile { .. } => None,
+                SpellEffect::ExplosiveProject { radius, .. } => Some(*radius),
+                SpellEffect::Area { radius, .. } => Some(*radius),
+            };
+
+            if radius.map(|r| r <= 0.0).unwrap_or(false) {
+                return invalid(format!(
+                    "spell {} level {} has invalid radius",
+                    spell.spell_code, level
+                ));
+            }
+
+            let motion = match &at_level.effect {
+                SpellEffect::Projectile { speed, ttl, .. }
+                | SpellEffect::ExplosiveProject { speed, ttl, .. } => Some((*speed, *ttl)),
+                SpellEffect::Area { .. } => None,
+            };
+
+            if motion
+                .map(|(speed, ttl)| speed <= 0.0 || ttl.as_seconds_f32() <= 0.0)
+                .unwrap_or(false)
+            {
+                return invalid(format!(
+                    "spell {} level {} has invalid projectile speed or ttl",
+                    spell.spell_code, level
+                ));
+            }
+
+            if !at_level.effect.status().is_none_or(is_valid_status) {
+                return invalid(format!(
+                    "spell {} level {} has invalid status",
+                    spell.spell_code, level
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_invalid(cfg: Cfg) {
+        match cfg.validate() {
+            Err(GameError::InvalidCfg(msg)) => log::info!("expected error {}", msg),
+            other => panic!("unexpected validation result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_cfg_is_valid() {
+        assert!(Cfg::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_cfg_ron_round_trip() {
+        let ron = Cfg::default().to_ron().unwrap();
+        let cfg = Cfg::from_ron(&ron).unwrap();
+        assert_eq!(Cfg::default().spells.len(), cfg.spells.len());
+        assert_eq!(Cfg::default().enemies.len(), cfg.enemies.len());
+    }
+
+    #[test]
+    fn test_cfg_json_round_trip() {
+        let json = serde_json::to_string(&Cfg::default()).unwrap();
+        let cfg = Cfg::from_json(&json).unwrap();
+        assert_eq!(Cfg::default().spells.len(), cfg.spells.len());
+    }
+
+    #[test]
+    fn test_load_cfg_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("cfg/default.ron");
+        let cfg = Cfg::load(&path).unwrap();
+        assert!(cfg.find_spell(SpellCode::from("firebold")).is_some());
+        assert!(cfg.find_enemy("enemy_1").is_some());
+    }
+
+    #[test]
+    fn test_cfg_file_matches_default() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("cfg/default.ron");
+        let cfg = Cfg::load(&path).unwrap();
+        assert_eq!(Cfg::default().to_ron().unwrap(), cfg.to_ron().unwrap());
+    }
+
+    #[test]
+    fn test_spawn_waves_repeat_and_speed_up() {
+        let spawn = Cfg::default().spawn;
+        let len = spawn.waves.len() as u32;
+
+        let first = spawn.get_wave(0).unwrap();
+        let repeated = spawn.get_wave(len).unwrap();
+        assert_eq!(5, first.enemies[0].count);
+        // 50% more on the first repetition
+        assert_eq!(8, repeated.enemies[0].count);
+
+        let start = spawn.get_interval(&first, TotalTime::default(), 0);
+        assert_eq!(first.interval, start);
+        let later = spawn.get_interval(&first, TotalTime::from(600.0), 0);
+        assert!(later.as_seconds_f32() < start.as_seconds_f32());
+        let leveled = spawn.get_interval(&first, TotalTime::default(), 10);
+        assert!(leveled.as_seconds_f32() < start.as_seconds_f32());
+        let min = spawn.get_interval(&first, TotalTime::from(100000.0), 100);
+        assert_eq!(spawn.min_interval, min);
+    }
+
+    #[test]
+    fn test_cfg_validation() {
+        let mut cfg = Cfg::default();
+        cfg.spells.push(cfg.spells[0].clone());
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.spells[0].per_level.clear();
+        assert_invalid(cfg);
+
+        for (speed, ttl) in [(0.0, 1.0), (-1.0, 1.0), (100.0, 0.0)] {
+            let mut cfg = Cfg::default();
+            cfg.spells[0].per_level[0].effect = SpellEffect::Projectile {
+                damage: 1.0,
+                speed,
+                ttl: DeltaTime(ttl),
+                status: None,
+            };
+            assert_invalid(cfg);
+        }
+
+        let mut cfg = Cfg::default();
+        cfg.spawn.waves[0].enemies[0].enemy = Arc::from("unknown");
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.spawn.waves[0].enemies[0].count = 0;
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.enemies[0].hp = 0.0;
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.enemies[0].ai = AiCfg::Swarmer(SwarmerAiCfg {
+            neighbour_radius: 0.0,
+            separation: 1.0,
+            cohesion: 1.0,
+            alignment: 1.0,
+        });
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.obstacles.push(ObstacleCfg {
+            model: Arc::from("rock"),
+            pos: V2::new(100.0, 100.0),
+            angle: 0.0,
+            shape: Shape::Aabb {
+                half_extents: V2::new(0.0, 10.0),
+            },
+            scale: 1.0,
+        });
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        cfg.enemies[0].status = Some(StatusEffectCfg {
+            kind: StatusKind::Slow,
+            duration: DeltaTime(1.0),
+            damage_per_second: 0.0,
+            slow: 2.0,
+        });
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        let boss = cfg
+            .enemies
+            .iter_mut()
+            .find_map(|e| e.boss.as_mut())
+            .unwrap();
+        boss.phases[1].hp_threshold = 1.0;
+        assert_invalid(cfg);
+
+        let mut cfg = Cfg::default();
+        let boss = cfg
+            .enemies
+            .iter_mut()
+            .find_map(|e| e.boss.as_mut())
+            .unwrap();
+        boss.phases[0].attack = Some(BossAttackCfg::Summon {
+            enemy: Arc::from("unknown"),
+            count: 1,
+        });
+        assert_invalid(cfg);
+
+        assert!(matches!(
+            Cfg::from_ron("(spells: [])"),
+            Err(GameError::InvalidCfg(_))
+        ));
+    }
 }
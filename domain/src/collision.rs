@@ -0,0 +1,548 @@
+use crate::components::{Collider, Position, Shape};
+use crate::models::V2;
+
+const EPSILON: f32 = 1e-6;
+
+/// shapes in world coordinates, circles and capsules are segments with a radius and boxes are
+/// oriented by its axes
+#[derive(Debug, Clone, Copy)]
+enum Geometry {
+    Rounded { a: V2, b: V2, radius: f32 },
+    Box { center: V2, axes: [V2; 2], half: V2 },
+}
+
+impl Geometry {
+    fn new(pos: &Position, col: &Collider) -> Geometry {
+        match &col.shape {
+            Shape::Circle => Geometry::Rounded {
+                a: pos.pos,
+                b: pos.pos,
+                radius: col.scale,
+            },
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => {
+                let offset = V2::from_angle(pos.angle) * *half_length * col.scale;
+                Geometry::Rounded {
+                    a: pos.pos - offset,
+                    b: pos.pos + offset,
+                    radius: *radius * col.scale,
+                }
+            }
+            Shape::Aabb { half_extents } => Geometry::Box {
+                center: pos.pos,
+                axes: [V2::X, V2::Y],
+                half: *half_extents * col.scale,
+            },
+            Shape::Rect { half_extents } => {
+                let u = V2::from_angle(pos.angle);
+                Geometry::Box {
+                    center: pos.pos,
+                    axes: [u, u.perp()],
+                    half: *half_extents * col.scale,
+                }
+            }
+        }
+    }
+}
+
+/// check if two colliders overlap, returning how much each one must move to stop overlapping
+pub fn resolve_collision(
+    pos1: &Position,
+    col1: &Collider,
+    pos2: &Position,
+    col2: &Collider,
+) -> Option<(V2, V2)> {
+    if let (Shape::Circle, Shape::Circle) = (&col1.shape, &col2.shape) {
+        let v = pos2.pos - pos1.pos;
+        let distance = v.length();
+        let impact_distance = col1.scale + col2.scale - distance;
+        return if impact_distance > 0.0 {
+            let v = v.try_normalize().unwrap_or(V2::X);
+            Some((v * -impact_distance, v * impact_distance))
+        } else {
+            None
+        };
+    }
+
+    let contact = match (Geometry::new(pos1, col1), Geometry::new(pos2, col2)) {
+        (
+            Geometry::Rounded {
+                a: a1,
+                b: b1,
+                radius: r1,
+            },
+            Geometry::Rounded {
+                a: a2,
+                b: b2,
+                radius: r2,
+            },
+        ) => rounded_vs_rounded(a1, b1, r1, a2, b2, r2),
+        (box1 @ Geometry::Box { .. }, box2 @ Geometry::Box { .. }) => box_vs_box(&box1, &box2),
+        (box1 @ Geometry::Box { .. }, Geometry::Rounded { a, b, radius }) => {
+            box_vs_rounded(&box1, a, b, radius)
+        }
+        (Geometry::Rounded { a, b, radius }, box2 @ Geometry::Box { .. }) => {
+            box_vs_rounded(&box2, a, b, radius).map(|(normal, depth)| (-normal, depth))
+        }
+    };
+
+    contact.map(|(normal, depth)| (normal * -depth, normal * depth))
+}
+
+/// closest points between segments p1-q1 and p2-q2
+fn closest_points_segments(p1: V2, q1: V2, p2: V2, q2: V2) -> (V2, V2) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= EPSILON && e <= EPSILON {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a <= EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            let mut s = if denom > EPSILON {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+fn closest_point_segment(a: V2, b: V2, p: V2) -> V2 {
+    closest_points_segments(a, b, p, p).0
+}
+
+/// contact normal from the first to the second shape and penetration depth
+fn rounded_vs_rounded(a1: V2, b1: V2, r1: f32, a2: V2, b2: V2, r2: f32) -> Option<(V2, f32)> {
+    let (p1, p2) = closest_points_segments(a1, b1, a2, b2);
+    let v = p2 - p1;
+    let distance = v.length();
+    let depth = r1 + r2 - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+
+    let normal = if distance > EPSILON {
+        v / distance
+    } else {
+        // segments cross, separate by the centers or across the first segment
+        let centers = (a2 + b2) * 0.5 - (a1 + b1) * 0.5;
+        centers
+            .try_normalize()
+            .or_else(|| (b1 - a1).perp().try_normalize())
+            .unwrap_or(V2::X)
+    };
+
+    Some((normal, depth))
+}
+
+/// center and half size of the box projected on the axis
+fn project_box(center: V2, axes: &[V2; 2], half: V2, axis: V2) -> (f32, f32) {
+    let extent = half.x * axes[0].dot(axis).abs() + half.y * axes[1].dot(axis).abs();
+    (center.dot(axis), extent)
+}
+
+/// find the axis of minimum overlap, None if any axis separates the shapes
+fn min_overlap(
+    axes: &[V2],
+    project1: impl Fn(V2) -> (f32, f32),
+    project2: impl Fn(V2) -> (f32, f32),
+) -> Option<(V2, f32)> {
+    let mut best: Option<(V2, f32)> = None;
+    for axis in axes {
+        let (c1, e1) = project1(*axis);
+        let (c2, e2) = project2(*axis);
+        let distance = c2 - c1;
+        let overlap = e1 + e2 - distance.abs();
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.map(|(_, depth)| overlap < depth).unwrap_or(true) {
+            let normal = if distance < 0.0 { -*axis } else { *axis };
+            best = Some((normal, overlap));
+        }
+    }
+    best
+}
+
+fn box_vs_box(box1: &Geometry, box2: &Geometry) -> Option<(V2, f32)> {
+    let (
+        Geometry::Box {
+            center: c1,
+            axes: axes1,
+            half: h1,
+        },
+        Geometry::Box {
+            center: c2,
+            axes: axes2,
+            half: h2,
+        },
+    ) = (box1, box2)
+    else {
+        return None;
+    };
+
+    min_overlap(
+        &[axes1[0], axes1[1], axes2[0], axes2[1]],
+        |axis| project_box(*c1, axes1, *h1, axis),
+        |axis| project_box(*c2, axes2, *h2, axis),
+    )
+}
+
+fn box_vs_rounded(box1: &Geometry, a: V2, b: V2, radius: f32) -> Option<(V2, f32)> {
+    let Geometry::Box { center, axes, half } = box1 else {
+        return None;
+    };
+
+    let project_segment = |axis: V2, radius: f32| {
+        let c = ((a + b) * 0.5).dot(axis);
+        let extent = ((b - a) * 0.5).dot(axis).abs() + radius;
+        (c, extent)
+    };
+
+    let mut sat_axes = vec![axes[0], axes[1]];
+    if let Some(n) = (b - a).perp().try_normalize() {
+        sat_axes.push(n);
+    }
+
+    let project_box = |axis: V2| project_box(*center, axes, *half, axis);
+
+    // a segment crossing the box may be far from its corners
+    if min_overlap(&sat_axes, project_box, |axis| project_segment(axis, 0.0)).is_some() {
+        return min_overlap(&sat_axes, project_box, |axis| project_segment(axis, radius));
+    }
+
+    let to_local = |p: V2| {
+        let v = p - *center;
+        V2::new(v.dot(axes[0]), v.dot(axes[1]))
+    };
+    let to_world = |p: V2| *center + axes[0] * p.x + axes[1] * p.y;
+
+    // closest points between a segment and a box are on an end of the segment or a corner
+    let mut candidates = vec![];
+    for p in [a, b] {
+        let local = to_local(p).clamp(-*half, *half);
+        candidates.push((to_world(local), p));
+    }
+    for corner in [
+        V2::new(-half.x, -half.y),
+        V2::new(half.x, -half.y),
+        V2::new(half.x, half.y),
+        V2::new(-half.x, half.y),
+    ] {
+        let corner = to_world(corner);
+        candidates.push((corner, closest_point_segment(a, b, corner)));
+    }
+
+    let (on_box, on_segment) = candidates.into_iter().min_by(|(p1, q1), (p2, q2)| {
+        p1.distance_squared(*q1)
+            .total_cmp(&p2.distance_squared(*q2))
+    })?;
+
+    let v = on_segment - on_box;
+    let distance = v.length();
+    if distance >= radius {
+        return None;
+    }
+
+    if distance <= EPSILON {
+        // the segment touches the border of the box
+        return min_overlap(&sat_axes, project_box, |axis| project_segment(axis, radius));
+    }
+
+    Some((v / distance, radius - distance))
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use approx::assert_abs_diff_eq;
+    use glam::Vec2;
+
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Position {
+        Position {
+            pos: V2::new(x, y),
+            angle: 0.0,
+        }
+    }
+
+    fn rotated(x: f32, y: f32, angle: f32) -> Position {
+        Position {
+            pos: V2::new(x, y),
+            angle,
+        }
+    }
+
+    fn collider(shape: Shape) -> Collider {
+        Collider {
+            shape,
+            ..Default::default()
+        }
+    }
+
+    fn circle(radius: f32) -> Collider {
+        Collider {
+            scale: radius,
+            ..Default::default()
+        }
+    }
+
+    fn aabb(hx: f32, hy: f32) -> Collider {
+        collider(Shape::Aabb {
+            half_extents: V2::new(hx, hy),
+        })
+    }
+
+    fn capsule(half_length: f32, radius: f32) -> Collider {
+        collider(Shape::Capsule {
+            half_length,
+            radius,
+        })
+    }
+
+    fn assert_contact(expected: V2, contact: Option<(V2, V2)>) {
+        let (v1, v2) = contact.expect("expected contact");
+        assert_abs_diff_eq!(expected.x, v2.x, epsilon = 0.001);
+        assert_abs_diff_eq!(expected.y, v2.y, epsilon = 0.001);
+        assert_abs_diff_eq!(-expected.x, v1.x, epsilon = 0.001);
+        assert_abs_diff_eq!(-expected.y, v1.y, epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_resolve_collision() {
+        let contact = resolve_collision(
+            &Position {
+                pos: Vec2::ZERO,
+                angle: 0.0,
+            },
+            &Collider {
+                shape: Shape::Circle,
+                scale: 10.0,
+                sensor: false,
+                ..Default::default()
+            },
+            &Position {
+                pos: V2::new(12.0, 5.0),
+                angle: 0.0,
+            },
+            &Collider {
+                shape: Shape::Circle,
+                scale: 5.0,
+                sensor: false,
+                ..Default::default()
+            },
+        );
+
+        assert!(contact.is_some());
+        let contact = contact.unwrap();
+
+        assert_abs_diff_eq!(-1.846154, contact.0.x);
+        assert_abs_diff_eq!(-0.7692308, contact.0.y);
+        assert_abs_diff_eq!(1.846154, contact.1.x);
+        assert_abs_diff_eq!(0.7692308, contact.1.y);
+    }
+
+    #[test]
+    fn test_resolve_collision_aabb_circle() {
+        // face
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(15.0, 0.0),
+            &circle(6.0),
+        );
+        assert_contact(V2::new(1.0, 0.0), contact);
+
+        // corner
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(13.0, 14.0),
+            &circle(6.0),
+        );
+        assert_contact(V2::new(0.6, 0.8), contact);
+
+        // circle first, center inside the box
+        let contact = resolve_collision(
+            &at(8.0, 0.0),
+            &circle(1.0),
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+        );
+        assert_contact(V2::new(-3.0, 0.0), contact);
+
+        // near the corner but outside
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(15.0, 15.0),
+            &circle(6.0),
+        );
+        assert!(contact.is_none());
+    }
+
+    #[test]
+    fn test_resolve_collision_aabb_aabb() {
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(12.0, 3.0),
+            &aabb(5.0, 5.0),
+        );
+        assert_contact(V2::new(3.0, 0.0), contact);
+
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(0.0, -14.0),
+            &aabb(5.0, 5.0),
+        );
+        assert_contact(V2::new(0.0, -1.0), contact);
+
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+            &at(16.0, 0.0),
+            &aabb(5.0, 5.0),
+        );
+        assert!(contact.is_none());
+    }
+
+    #[test]
+    fn test_resolve_collision_rect() {
+        // a square rotated 45 degrees reaches 14.14 on the x axis
+        let rect = collider(Shape::Rect {
+            half_extents: V2::new(10.0, 10.0),
+        });
+        let contact = resolve_collision(
+            &rotated(0.0, 0.0, PI * 0.25),
+            &rect,
+            &at(18.0, 0.0),
+            &aabb(5.0, 5.0),
+        );
+        assert_contact(V2::new(1.142, 0.0), contact);
+
+        let contact = resolve_collision(&at(0.0, 0.0), &rect, &at(18.0, 0.0), &aabb(5.0, 5.0));
+        assert!(contact.is_none());
+
+        // a long rect rotated 90 degrees is vertical
+        let rect = collider(Shape::Rect {
+            half_extents: V2::new(20.0, 2.0),
+        });
+        let contact = resolve_collision(
+            &rotated(0.0, 0.0, PI * 0.5),
+            &rect,
+            &at(0.0, 22.0),
+            &circle(3.0),
+        );
+        assert_contact(V2::new(0.0, 1.0), contact);
+        let contact = resolve_collision(
+            &rotated(0.0, 0.0, PI * 0.5),
+            &rect,
+            &at(4.0, 0.0),
+            &circle(3.0),
+        );
+        assert_contact(V2::new(1.0, 0.0), contact);
+    }
+
+    #[test]
+    fn test_resolve_collision_capsule() {
+        // capsule and circle
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &capsule(10.0, 2.0),
+            &at(5.0, 4.0),
+            &circle(3.0),
+        );
+        assert_contact(V2::new(0.0, 1.0), contact);
+
+        // beyond the end of the capsule
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &capsule(10.0, 2.0),
+            &at(14.0, 0.0),
+            &circle(3.0),
+        );
+        assert_contact(V2::new(1.0, 0.0), contact);
+
+        // parallel capsules
+        let contact = resolve_collision(
+            &at(0.0, 0.0),
+            &capsule(10.0, 2.0),
+            &at(5.0, 3.0),
+            &capsule(10.0, 2.0),
+        );
+        assert_contact(V2::new(0.0, 1.0), contact);
+
+        // rotated capsule
+        let contact = resolve_collision(
+            &rotated(0.0, 0.0, PI * 0.5),
+            &capsule(10.0, 2.0),
+            &at(3.0, 8.0),
+            &circle(2.0),
+        );
+        assert_contact(V2::new(1.0, 0.0), contact);
+
+        // capsule over a box
+        let contact = resolve_collision(
+            &at(0.0, 13.0),
+            &capsule(5.0, 4.0),
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+        );
+        assert_contact(V2::new(0.0, -1.0), contact);
+
+        // long capsule crossing the box, far from its corners
+        let contact = resolve_collision(
+            &rotated(0.0, 8.0, PI * 0.5),
+            &capsule(100.0, 1.0),
+            &at(0.0, 0.0),
+            &aabb(50.0, 10.0),
+        );
+        assert_contact(V2::new(-51.0, 0.0), contact);
+
+        let contact = resolve_collision(
+            &at(0.0, 13.0),
+            &capsule(5.0, 2.0),
+            &at(0.0, 0.0),
+            &aabb(10.0, 10.0),
+        );
+        assert!(contact.is_none());
+    }
+
+    #[test]
+    fn test_resolve_collision_scale() {
+        let mut col = aabb(1.0, 1.0);
+        col.scale = 10.0;
+        let contact = resolve_collision(&at(0.0, 0.0), &col, &at(15.0, 0.0), &circle(6.0));
+        assert_contact(V2::new(1.0, 0.0), contact);
+    }
+}
@@ -1,30 +1,44 @@
+// the ConvertSaveload derive expects NoError in scope
+use std::convert::Infallible as NoError;
+
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
-use specs_derive::Component;
+use specs::saveload::{ConvertSaveload, Marker};
+use specs_derive::{Component, ConvertSaveload};
 
+use crate::cfg::{AiCfg, BossCfg, BossPhaseCfg, ChargerAiCfg, RangedAiCfg, SwarmerAiCfg};
 use crate::models::*;
+use crate::status::StatusEffectCfg;
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Position {
     pub pos: V2,
     pub angle: Radians,
 }
 
-#[derive(Component, Debug, Clone, Default)]
+/// position at the start of the last tick, used to interpolate the rendering
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrevPosition {
+    pub pos: V2,
+    pub angle: Radians,
+}
+
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Velocity {
     pub vel: V2,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct HasModel {
     pub model: Model,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Critter {
     pub speed: Speed,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Frame {
     pub tick: Tick,
     pub delta_time: DeltaTime,
@@ -39,56 +53,260 @@ impl Frame {
     }
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Damageable {
     pub hp: Hp,
     pub max_hp: Hp,
     pub kill_score: Score,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct DamageCollider {
     pub damage: Damage,
     /// only objects of this team will receive damage
     pub affects: Team,
     /// is removed after hit
     pub disposable: bool,
+    /// applied to the hit target
+    #[serde(default)]
+    pub status: Option<StatusEffectCfg>,
+}
+
+/// explode when touching the affected team or when the deadline expires
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Explosive {
+    pub damage: Damage,
+    pub radius: Radius,
+    /// only objects of this team will receive damage
+    pub affects: Team,
+    #[serde(default)]
+    pub status: Option<StatusEffectCfg>,
+}
+
+/// apply damage to all objects of the affected team inside the radius
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Area {
+    pub damage: Damage,
+    pub radius: Radius,
+    /// only objects of this team will receive damage
+    pub affects: Team,
+    /// apply the damage once and is removed, otherwise damage is applied per second until
+    /// the deadline
+    pub instant: bool,
+    #[serde(default)]
+    pub status: Option<StatusEffectCfg>,
 }
 
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Deadline {
     pub deadline: TotalTime,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub enum Ai {
+    /// walk straight to the player
     FollowPlayer,
+    /// walk to the player around obstacles, using the flow field of the Navigation
+    FollowFlowField,
+    Ranged(RangedAiCfg),
+    Charger {
+        cfg: ChargerAiCfg,
+        state: ChargeState,
+    },
+    Swarmer(SwarmerAiCfg),
+}
+
+impl Ai {
+    pub fn new(cfg: &AiCfg) -> Ai {
+        match cfg {
+            AiCfg::Melee => Ai::FollowFlowField,
+            AiCfg::Ranged(cfg) => Ai::Ranged(cfg.clone()),
+            AiCfg::Charger(cfg) => Ai::Charger {
+                cfg: cfg.clone(),
+                state: ChargeState::Approach,
+            },
+            AiCfg::Swarmer(cfg) => Ai::Swarmer(cfg.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChargeState {
+    Approach,
+    /// stand still facing the dash direction
+    WindUp {
+        until: TotalTime,
+        dir: V2,
+    },
+    Dash {
+        until: TotalTime,
+        dir: V2,
+    },
+    Recover {
+        until: TotalTime,
+    },
+}
+
+/// scripted enemy, the phases of its cfg are entered as it loses hp
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {
+    pub cfg: BossCfg,
+    /// speed of the enemy cfg, multiplied by the phase
+    pub base_speed: Speed,
+    /// none until the first phase is entered
+    pub phase: Option<usize>,
+    pub next_attack: TotalTime,
+}
+
+impl Boss {
+    pub fn new(cfg: &BossCfg, base_speed: Speed) -> Self {
+        Boss {
+            cfg: cfg.clone(),
+            base_speed,
+            phase: None,
+            next_attack: TotalTime::default(),
+        }
+    }
+
+    /// last phase whose threshold is reached by the hp fraction
+    pub fn phase_for(&self, hp_fraction: f32) -> Option<usize> {
+        self.cfg
+            .phases
+            .iter()
+            .rposition(|phase| hp_fraction <= phase.hp_threshold)
+    }
+
+    pub fn current_phase(&self) -> Option<&BossPhaseCfg> {
+        self.phase.and_then(|index| self.cfg.phases.get(index))
+    }
 }
 
-#[derive(Component, Debug, Clone)]
+/// static object that blocks critters and projectiles, like rocks and pillars
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+#[storage(NullStorage)]
+pub struct Obstacle;
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Collider {
     pub shape: Shape,
     pub scale: f32,
+    /// detect contacts without pushing or being pushed
     pub sensor: bool,
+    /// penetration is split by inverse mass, heavier bodies move less
+    pub mass: Mass,
+    /// never moved by collisions, like walls and obstacles
+    pub is_static: bool,
+    /// layers this collider belongs to
+    pub layer: CollisionMask,
+    /// layers this collider interacts with, both colliders must accept each other
+    pub mask: CollisionMask,
+}
+
+pub type CollisionMask = u32;
+
+/// collision layers, see Collider::layer
+pub mod layers {
+    use super::CollisionMask;
+
+    pub const PLAYER: CollisionMask = 1 << 0;
+    pub const ENEMY: CollisionMask = 1 << 1;
+    pub const PROJECTILE: CollisionMask = 1 << 2;
+    pub const PICKUP: CollisionMask = 1 << 3;
+    pub const OBSTACLE: CollisionMask = 1 << 4;
+    pub const ALL: CollisionMask = CollisionMask::MAX;
+
+    pub const PLAYER_MASK: CollisionMask = ENEMY | PROJECTILE | PICKUP | OBSTACLE;
+    pub const ENEMY_MASK: CollisionMask = PLAYER | ENEMY | PROJECTILE | OBSTACLE;
+    /// projectiles ignore each other
+    pub const PROJECTILE_MASK: CollisionMask = PLAYER | ENEMY | OBSTACLE;
+    pub const PICKUP_MASK: CollisionMask = PLAYER;
+    pub const OBSTACLE_MASK: CollisionMask = ALL;
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Collider {
+            shape: Shape::Circle,
+            scale: 1.0,
+            sensor: false,
+            mass: 1.0,
+            is_static: false,
+            layer: layers::ALL,
+            mask: layers::ALL,
+        }
+    }
+}
+
+impl Collider {
+    /// both colliders must have the layer of the other in its mask
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        (self.layer & other.mask) != 0 && (other.layer & self.mask) != 0
+    }
+
+    pub fn inverse_mass(&self) -> f32 {
+        if self.is_static || self.mass <= 0.0 {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+
+    /// radius of a circle containing the whole shape
+    pub fn bounding_radius(&self) -> f32 {
+        let radius = match &self.shape {
+            Shape::Circle => 1.0,
+            Shape::Aabb { half_extents } | Shape::Rect { half_extents } => half_extents.length(),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => half_length + radius,
+        };
+        radius * self.scale
+    }
 }
 
-#[derive(Component, Debug, Clone)]
+/// shapes are multiplied by the collider scale, circle radius is the scale itself
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub enum Shape {
     Circle,
+    /// axis aligned box, ignores the position angle
+    Aabb {
+        half_extents: V2,
+    },
+    /// segment along the position angle with rounded ends
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+    /// box rotated by the position angle
+    Rect {
+        half_extents: V2,
+    },
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Component)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
 pub enum Team {
     Player,
     Enemy,
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Component)]
+impl Team {
+    /// the team affected by the spells of this team
+    pub fn opponent(&self) -> Team {
+        match self {
+            Team::Player => Team::Enemy,
+            Team::Enemy => Team::Player,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Component, ConvertSaveload)]
 pub struct Owner {
     pub entity: Entity,
 }
 
 #[cfg(test)]
 mod test {
+    #[allow(unused_imports)]
     use super::*;
 }
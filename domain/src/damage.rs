@@ -1,9 +1,10 @@
 use specs::prelude::*;
 use specs::Entity;
 
-use crate::events::Events;
-use crate::models::Hp;
+use crate::events::{Events, GameEvent};
+use crate::models::{Hp, Radius, TotalTime, V2};
 use crate::player::Player;
+use crate::status::{StatusEffectCfg, StatusEffects};
 use crate::unwrap_or_return;
 
 use super::components::*;
@@ -13,6 +14,30 @@ pub struct Hit {
     pub source: Entity,
     pub target: Entity,
     pub amount: Hp,
+    /// applied to the target before the damage
+    pub status: Option<StatusEffectCfg>,
+}
+
+/// check if an object touch the radius, taking its collider in account
+pub fn is_in_radius(center: V2, radius: Radius, pos: V2, collider: Option<&Collider>) -> bool {
+    let distance = pos.distance(center) - collider.map(|c| c.bounding_radius()).unwrap_or(0.0);
+    distance <= radius
+}
+
+/// apply the status of the hit, the owner of the source is credited with its damage
+pub fn apply_status(
+    hit: &Hit,
+    now: TotalTime,
+    owners: &ReadStorage<Owner>,
+    statuses: &mut WriteStorage<StatusEffects>,
+) {
+    let status = unwrap_or_return!(hit.status.as_ref());
+    let effects = unwrap_or_return!(statuses.get_mut(hit.target));
+    let source = owners
+        .get(hit.source)
+        .map(|owner| owner.entity)
+        .unwrap_or(hit.source);
+    effects.apply(status, source, now);
 }
 
 pub fn process_hit(
@@ -20,22 +45,68 @@ pub fn process_hit(
     entities: &Entities,
     events: &mut WriteExpect<Events>,
     owners: &ReadStorage<Owner>,
+    bosses: &ReadStorage<Boss>,
     players: &mut WriteStorage<Player>,
     damageables: &mut WriteStorage<Damageable>,
 ) {
     log::trace!("{:?} receive {:?}", hit.target, hit);
 
     let damageable = unwrap_or_return!(damageables.get_mut(hit.target));
+    if damageable.hp < 0.0 {
+        // already died by a previous hit on this frame
+        return;
+    }
     damageable.hp -= hit.amount;
 
+    events.game_events.push(GameEvent::Damaged {
+        target: hit.target,
+        source: hit.source,
+        amount: hit.amount,
+    });
+
     if damageable.hp < 0.0 {
-        log::trace!("{:?} died, deleting it", hit.target);
-        entities.delete(hit.target).unwrap();
-        events.removed.push(hit.target);
+        if players.contains(hit.target) {
+            // player is kept, the game state is changed to player dead
+            log::debug!("{:?} player died", hit.target);
+        } else {
+            log::trace!("{:?} died, deleting it", hit.target);
+            entities.delete(hit.target).unwrap();
+            events.removed.push(hit.target);
+        }
+
+        let killer = owners
+            .get(hit.source)
+            .map(|owner| owner.entity)
+            .unwrap_or(hit.source);
+
+        events.game_events.push(GameEvent::Killed {
+            entity: hit.target,
+            killer,
+            score: damageable.kill_score,
+        });
+
+        if players.contains(hit.target) {
+            events
+                .game_events
+                .push(GameEvent::PlayerDied { player: hit.target });
+        }
+
+        let mut score = damageable.kill_score;
+        if let Some(boss) = bosses.get(hit.target) {
+            events.game_events.push(GameEvent::BossDefeated {
+                boss: hit.target,
+                killer,
+                score: boss.cfg.defeat_score,
+            });
+            score += boss.cfg.defeat_score;
+        }
 
-        if let Some(owner) = owners.get(hit.source) {
-            if let Some(player) = players.get_mut(owner.entity) {
-                player.update_score(damageable.kill_score);
+        if let Some(player) = players.get_mut(killer) {
+            if let Some(level) = player.update_score(score) {
+                events.game_events.push(GameEvent::LevelUp {
+                    player: killer,
+                    level,
+                });
             }
         }
     }
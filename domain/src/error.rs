@@ -2,4 +2,6 @@
 pub enum GameError {
     Msg(String),
     Str(&'static str),
+    /// configuration could not be parsed or is not valid
+    InvalidCfg(String),
 }
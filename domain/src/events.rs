@@ -1,16 +1,109 @@
 use specs::Entity;
 
+use crate::caster::CastError;
+use crate::models::{Damage, Level, Mana, Radius, Score, V2};
+use crate::player::UpgradeError;
+use crate::spell::SpellCode;
+
+/// feedback to the player about requests that could not be applied
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feedback {
+    CastFailed(CastError),
+    UpgradeFailed(UpgradeError),
+}
+
+#[derive(Debug, Clone)]
+pub struct Explosion {
+    pub pos: V2,
+    pub radius: Radius,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    Damaged {
+        target: Entity,
+        source: Entity,
+        amount: Damage,
+    },
+    Killed {
+        entity: Entity,
+        /// the owner of the source of the hit, or the source itself when it has no owner
+        killer: Entity,
+        score: Score,
+    },
+    /// the player started a cast, the mana is deducted at this point even if the cast does not
+    /// complete
+    CastStarted {
+        caster: Entity,
+        spell: SpellCode,
+        mana: Mana,
+    },
+    SpellCast {
+        caster: Entity,
+        spell: SpellCode,
+    },
+    LevelUp {
+        player: Entity,
+        level: Level,
+    },
+    /// caster has no mana to cast any of its spells
+    ManaDepleted {
+        caster: Entity,
+    },
+    /// a projectile was removed without hitting anything, by its deadline, an obstacle or by
+    /// leaving the arena
+    ProjectileExpired {
+        entity: Entity,
+    },
+    PlayerDied {
+        player: Entity,
+    },
+    /// the boss entered a phase of its cfg, phases are numbered from 0
+    BossPhaseChanged {
+        boss: Entity,
+        phase: u32,
+    },
+    /// sent after the Killed event of a boss, the score is granted on top of the kill score
+    BossDefeated {
+        boss: Entity,
+        killer: Entity,
+        score: Score,
+    },
+    /// first spawn of the wave, waves are numbered from 1
+    WaveStarted {
+        wave: u32,
+        boss: bool,
+    },
+    /// all enemies of the wave are dead
+    WaveCleared {
+        wave: u32,
+    },
+}
+
 #[derive(Debug, Default)]
 pub struct Events {
     pub added: Vec<Entity>,
     pub removed: Vec<Entity>,
+    pub explosions: Vec<Explosion>,
+    pub feedback: Vec<Feedback>,
+    pub game_events: Vec<GameEvent>,
 }
 
 impl Events {
+    #[allow(clippy::mem_replace_with_default, clippy::let_and_return)]
     pub fn take(&mut self) -> Events {
         let added = std::mem::replace(&mut self.added, Vec::new());
         let removed = std::mem::replace(&mut self.removed, Vec::new());
-        let result = Events { added, removed };
+        let explosions = std::mem::replace(&mut self.explosions, Vec::new());
+        let feedback = std::mem::replace(&mut self.feedback, Vec::new());
+        let game_events = std::mem::replace(&mut self.game_events, Vec::new());
+        let result = Events {
+            added,
+            removed,
+            explosions,
+            feedback,
+            game_events,
+        };
         result
     }
 }
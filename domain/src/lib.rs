@@ -1,8 +1,6 @@
-use std::ops::Deref;
-
 use log::LevelFilter;
-use rand::prelude::StdRng;
 use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
 use shred::Fetch;
 use specs::prelude::*;
 
@@ -11,11 +9,17 @@ use crate::components::*;
 use crate::error::GameError;
 use crate::events::Events;
 use crate::models::*;
-use crate::player::{Player, PlayerInput, PlayerSystem};
+use crate::nav::Navigation;
+use crate::player::{Player, PlayerDeathSystem, PlayerInput, PlayerSystem, PlayerUpgradeSystem};
+use crate::replay::{Replay, ReplayFrame};
+use crate::snapshot::{SavedMarker, SavedMarkerAllocator, Snapshot};
+use crate::status::StatusEffects;
 use crate::systems::*;
 
+pub mod brain;
 pub mod caster;
 pub mod cfg;
+pub mod collision;
 pub mod components;
 pub mod damage;
 pub mod error;
@@ -23,8 +27,14 @@ pub mod events;
 pub mod loader;
 pub mod math;
 pub mod models;
+pub mod nav;
 pub mod player;
+pub mod replay;
+pub mod simulation;
+pub mod snapshot;
+pub mod spatial;
 pub mod spell;
+pub mod status;
 pub mod systems;
 pub mod utils;
 
@@ -51,6 +61,10 @@ macro_rules! unwrap_or_return {
 pub struct Api {
     pub world: World,
     pub enemy_system: EnemySpawnerSystem,
+    /// when defined, update accumulate the real time and run fixed ticks
+    pub fixed_timestep: Option<FixedTimestep>,
+    /// when defined, every update is recorded into the replay
+    pub recording: Option<Replay>,
 }
 
 impl Default for Api {
@@ -63,22 +77,32 @@ impl Default for Api {
 
         let mut world = World::new();
         world.register::<Position>();
+        world.register::<PrevPosition>();
         world.register::<Velocity>();
         world.register::<Player>();
         world.register::<Critter>();
         world.register::<Caster>();
         world.register::<DamageCollider>();
         world.register::<Deadline>();
+        world.register::<Explosive>();
+        world.register::<Area>();
         world.register::<HasModel>();
         world.register::<Ai>();
+        world.register::<Boss>();
+        world.register::<StatusEffects>();
+        world.register::<Obstacle>();
         world.register::<Collider>();
         world.register::<Damageable>();
         world.register::<Team>();
         world.register::<Owner>();
+        world.register::<SavedMarker>();
+        world.insert(SavedMarkerAllocator::new());
 
         Self {
             world,
             enemy_system: EnemySpawnerSystem::default(),
+            fixed_timestep: None,
+            recording: None,
         }
     }
 }
@@ -92,17 +116,55 @@ impl Api {
 
         self.world.insert(Frame::default());
         self.world.insert(Events::default());
-        self.world.insert(StdRng::seed_from_u64(params.seed));
+        self.world.insert(ChaCha12Rng::seed_from_u64(params.seed));
         self.world.insert(Contacts::default());
+        self.world.insert(Navigation::default());
+        self.world.insert(GameState::default());
+        if self.recording.is_some() {
+            self.recording = Some(Replay::new(&params));
+        }
         self.world.insert(params);
 
         self.enemy_system = Default::default();
+        if let Some(fixed_timestep) = &self.fixed_timestep {
+            self.fixed_timestep = Some(FixedTimestep::new(fixed_timestep.delta_time));
+        }
 
         loader::load_player(&mut self.world, start_position);
 
+        let obstacles = self
+            .world
+            .read_resource::<SceneryParams>()
+            .cfg
+            .obstacles
+            .clone();
+        for obstacle in &obstacles {
+            let e = loader::new_obstacle(self.world.create_entity(), obstacle).build();
+            self.world.write_resource::<Events>().added.push(e);
+        }
+
+        Ok(())
+    }
+
+    /// remove all entities and start the current scenery again, removed entities are reported
+    /// on the events
+    pub fn restart(&mut self) -> Result<(), GameError> {
+        let params = (*self.get_scenery_params()).clone();
+
+        let removed: Vec<Entity> = self.world.entities().join().collect();
+        self.world.delete_all();
+        self.world.maintain();
+
+        self.start_scenery(params)?;
+        self.world.write_resource::<Events>().removed = removed;
+
         Ok(())
     }
 
+    pub fn get_game_state(&self) -> GameState {
+        (*self.world.read_resource::<GameState>()).clone()
+    }
+
     pub fn set_player_input(&mut self, input: PlayerInput) -> Result<(), GameError> {
         let mut player_repo = self.world.write_storage::<Player>();
         for (pla,) in (&mut player_repo,).join() {
@@ -111,15 +173,191 @@ impl Api {
         Ok(())
     }
 
+    /// stop the simulation, player input and upgrade requests are still processed
+    pub fn pause(&mut self) {
+        let mut state = self.world.write_resource::<GameState>();
+        if state.is_running() {
+            *state = GameState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        let mut state = self.world.write_resource::<GameState>();
+        if *state == GameState::Paused {
+            *state = GameState::Running;
+        }
+    }
+
+    /// advance exactly n fixed ticks, also when paused
+    pub fn step(&mut self, n: u32) -> Result<(), GameError> {
+        for _ in 0..n {
+            if matches!(self.get_game_state(), GameState::PlayerDead(_)) {
+                break;
+            }
+            let step = self
+                .fixed_timestep
+                .as_ref()
+                .map(|ts| ts.delta_time)
+                .unwrap_or(FIXED_DELTA_TIME);
+            self.run_tick(step)?;
+        }
+        Ok(())
+    }
+
+    /// capture the full state of the scenery, including the rng, the game continues the same
+    /// way after loading the snapshot and saving does not change it
+    pub fn save_snapshot(&self) -> Result<Snapshot, GameError> {
+        let components = snapshot::save_components(&self.world)?;
+
+        Ok(Snapshot {
+            params: (*self.get_scenery_params()).clone(),
+            frame: (*self.world.read_resource::<Frame>()).clone(),
+            game_state: self.get_game_state(),
+            rng: (*self.world.read_resource::<ChaCha12Rng>()).clone(),
+            spawner: self.enemy_system.clone(),
+            components,
+        })
+    }
+
+    /// replace the current scenery by the snapshot, all current entities are reported as removed
+    /// and the loaded ones as added
+    pub fn load_snapshot(&mut self, snapshot: &Snapshot) -> Result<(), GameError> {
+        if self.recording.take().is_some() {
+            log::warn!("loading a snapshot, stopping recording");
+        }
+
+        let removed: Vec<Entity> = self.world.entities().join().collect();
+        self.world.delete_all();
+        self.world.maintain();
+
+        self.world.insert(snapshot.frame.clone());
+        self.world.insert(Events::default());
+        self.world.insert(snapshot.rng.clone());
+        self.world.insert(Contacts::default());
+        self.world.insert(Navigation::default());
+        self.world.insert(snapshot.game_state.clone());
+        self.world.insert(snapshot.params.clone());
+
+        self.enemy_system = snapshot.spawner.clone();
+
+        snapshot::load_components(&mut self.world, &snapshot.components)?;
+        snapshot::clear_markers(&self.world);
+        self.world.maintain();
+
+        let added: Vec<Entity> = self.world.entities().join().collect();
+        let mut events = self.world.write_resource::<Events>();
+        events.removed = removed;
+        events.added = added;
+
+        Ok(())
+    }
+
+    /// record the session from the start, starting a scenery or restarting it begins a new
+    /// replay
+    pub fn start_recording(&mut self) -> Result<(), GameError> {
+        if self.world.read_resource::<Frame>().tick != 0 {
+            return Err(GameError::Str("recording must start before the first tick"));
+        }
+        let replay = Replay::new(&self.get_scenery_params());
+        self.recording = Some(replay);
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    /// replace the current scenery by the replay one and run all recorded frames, the final world
+    /// state is the same as the recorded session. Previous entities are reported as removed
+    pub fn play_replay(&mut self, replay: &Replay) -> Result<(), GameError> {
+        let removed: Vec<Entity> = self.world.entities().join().collect();
+        self.world.delete_all();
+        self.world.maintain();
+
+        self.start_scenery(replay.get_scenery_params())?;
+        self.world.write_resource::<Events>().removed = removed;
+
+        for frame in &replay.frames {
+            self.set_player_input(frame.input.clone())?;
+            match frame.delta_time {
+                Some(delta_time) => self.run_tick(delta_time)?,
+                None => self.run_paused(),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// enable the fixed timestep mode with the given tick delta time, or disable it with None
+    pub fn set_fixed_timestep(&mut self, delta_time: Option<DeltaTime>) {
+        self.fixed_timestep = delta_time.map(FixedTimestep::new);
+    }
+
+    /// interpolation factor between PrevPosition and Position, always 1.0 without fixed timestep
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.fixed_timestep
+            .as_ref()
+            .map(|ts| ts.alpha())
+            .unwrap_or(1.0)
+    }
+
     pub fn update(&mut self, delta_time: DeltaTime) -> Result<(), GameError> {
+        match self.get_game_state() {
+            GameState::Running => match self.fixed_timestep.as_mut() {
+                Some(fixed_timestep) => {
+                    let ticks = fixed_timestep.advance(delta_time);
+                    let step = fixed_timestep.delta_time;
+                    for _ in 0..ticks {
+                        if !self.get_game_state().is_running() {
+                            break;
+                        }
+                        self.run_tick(step)?;
+                    }
+                    Ok(())
+                }
+                None => self.run_tick(delta_time),
+            },
+            GameState::Paused => {
+                self.run_paused();
+                Ok(())
+            }
+            GameState::PlayerDead(_) => Ok(()),
+        }
+    }
+
+    #[allow(clippy::unnecessary_mut_passed)]
+    fn run_paused(&mut self) {
+        self.record_frame(None);
+
+        let mut system = PlayerUpgradeSystem {};
+        system.run_now(&mut self.world);
+    }
+
+    fn record_frame(&mut self, delta_time: Option<DeltaTime>) {
+        let replay = unwrap_or_return!(self.recording.as_mut());
+        let players = self.world.read_storage::<Player>();
+        let input = unwrap_or_return!((&players,).join().next()).0.input.clone();
+        replay.frames.push(ReplayFrame { input, delta_time });
+    }
+
+    #[allow(clippy::unnecessary_mut_passed)]
+    fn run_tick(&mut self, delta_time: DeltaTime) -> Result<(), GameError> {
+        self.record_frame(Some(delta_time));
+
         {
             let mut frame = self.world.write_resource::<Frame>();
             frame.update(delta_time);
         }
 
+        let mut system = PrevPositionSystem {};
+        system.run_now(&mut self.world);
+
         let mut system = DeadlineSystem {};
         system.run_now(&mut self.world);
 
+        let mut system = PlayerUpgradeSystem {};
+        system.run_now(&mut self.world);
+
         let mut system = PlayerSystem {};
         system.run_now(&mut self.world);
 
@@ -129,19 +367,43 @@ impl Api {
         let mut system = ColliderSystem {};
         system.run_now(&mut self.world);
 
+        let mut system = ArenaSystem {};
+        system.run_now(&mut self.world);
+
         let mut system = DamageColliderSystem {};
         system.run_now(&mut self.world);
 
+        let mut system = ExplosiveSystem {};
+        system.run_now(&mut self.world);
+
+        let mut system = AreaSystem {};
+        system.run_now(&mut self.world);
+
+        let mut system = StatusSystem {};
+        system.run_now(&mut self.world);
+
         let mut system = CasterSystem {};
         system.run_now(&mut self.world);
 
         self.enemy_system.run_now(&mut self.world);
 
+        let mut system = BossSystem {};
+        system.run_now(&mut self.world);
+
+        let mut system = NavigationSystem {};
+        system.run_now(&mut self.world);
+
         let mut system = AiSystem {};
         system.run_now(&mut self.world);
 
+        let mut system = PlayerDeathSystem {};
+        system.run_now(&mut self.world);
+
         self.world.maintain();
 
+        let mut system = DeadReferencesSystem {};
+        system.run_now(&mut self.world);
+
         Ok(())
     }
 
@@ -150,7 +412,7 @@ impl Api {
         events.take()
     }
 
-    pub fn get_scenery_params(&self) -> Fetch<SceneryParams> {
+    pub fn get_scenery_params(&self) -> Fetch<'_, SceneryParams> {
         self.world.read_resource::<SceneryParams>()
     }
 }
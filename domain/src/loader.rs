@@ -5,34 +5,49 @@ use specs::prelude::*;
 
 use crate::caster::Caster;
 use crate::cfg;
+use crate::cfg::{EnemyCasterCfg, EnemyCfg, ObstacleCfg};
 use crate::models::*;
 use crate::player::Player;
+use crate::status::StatusEffects;
 
 use super::components::*;
 
 pub fn load_player(world: &mut World, pos: V2) -> Entity {
-    let caster = Caster::new(&world.read_resource::<SceneryParams>().cfg.spells);
+    let cfg = world.read_resource::<SceneryParams>().cfg.clone();
+
+    let mut caster = Caster::new(&cfg.spells);
+    caster.max_mana = cfg.player.max_mana;
+    caster.mana = cfg.player.max_mana;
+    caster.mana_recharge = cfg.player.mana_recharge;
+    caster.casting_skill = cfg.player.casting_skill;
 
     world
         .create_entity()
         .with(Position { pos, angle: 0.0 })
         .with(Velocity::default())
-        .with(Player::default())
+        .with(Player::new(cfg.player.skill_points))
         .with(Team::Player)
         .with(Damageable {
-            hp: 100.0,
-            max_hp: 100.0,
+            hp: cfg.player.hp,
+            max_hp: cfg.player.hp,
             kill_score: 0,
         })
-        .with(Critter { speed: 100.0 })
+        .with(Critter {
+            speed: cfg.player.speed,
+        })
         .with(HasModel {
-            model: Arc::from("player"),
+            model: cfg.player.model.clone(),
         })
         .with(caster)
+        .with(StatusEffects::default())
         .with(Collider {
             shape: Shape::Circle,
-            scale: 12.0,
+            scale: cfg.player.collider_radius,
             sensor: false,
+            mass: cfg.player.mass,
+            is_static: false,
+            layer: layers::PLAYER,
+            mask: layers::PLAYER_MASK,
         })
         .build()
 }
@@ -42,17 +57,13 @@ pub fn create_magic_missile<B: Builder>(
     owner: Option<Entity>,
     pos: Position,
     dir: Vec2,
-    damage: Damage,
     speed: Speed,
+    damage: DamageCollider,
     deadline: TotalTime,
 ) -> B {
     builder
         .with(pos)
-        .with(DamageCollider {
-            damage,
-            affects: Team::Enemy,
-            disposable: true,
-        })
+        .with(damage)
         .with(Velocity { vel: dir * speed })
         .with(HasModel {
             model: Arc::from(cfg::MODEL_MAGIC_MISSILE),
@@ -61,36 +72,121 @@ pub fn create_magic_missile<B: Builder>(
             shape: Shape::Circle,
             scale: 2.5,
             sensor: true,
+            layer: layers::PROJECTILE,
+            mask: layers::PROJECTILE_MASK,
+            ..Default::default()
+        })
+        .with(Deadline { deadline })
+        .maybe_with(owner.map(|own| Owner { entity: own }))
+}
+
+pub fn create_explosive_missile<B: Builder>(
+    builder: B,
+    owner: Option<Entity>,
+    pos: Position,
+    dir: Vec2,
+    speed: Speed,
+    explosive: Explosive,
+    deadline: TotalTime,
+) -> B {
+    builder
+        .with(pos)
+        .with(explosive)
+        .with(Velocity { vel: dir * speed })
+        .with(HasModel {
+            model: Arc::from(cfg::MODEL_FIREBALL),
+        })
+        .with(Collider {
+            shape: Shape::Circle,
+            scale: 4.0,
+            sensor: true,
+            layer: layers::PROJECTILE,
+            mask: layers::PROJECTILE_MASK,
+            ..Default::default()
         })
         .with(Deadline { deadline })
         .maybe_with(owner.map(|own| Owner { entity: own }))
 }
 
-pub fn new_critter<B: Builder>(builder: B, pos: Position) -> B {
+pub fn create_area<B: Builder>(
+    builder: B,
+    owner: Option<Entity>,
+    pos: V2,
+    area: Area,
+    deadline: Option<TotalTime>,
+) -> B {
+    builder
+        .with(Position { pos, angle: 0.0 })
+        .with(area)
+        .with(HasModel {
+            model: Arc::from(cfg::MODEL_AREA),
+        })
+        .maybe_with(deadline.map(|deadline| Deadline { deadline }))
+        .maybe_with(owner.map(|own| Owner { entity: own }))
+}
+
+pub fn new_critter<B: Builder>(builder: B, pos: Position, enemy: &EnemyCfg) -> B {
     builder
         .with(pos)
         .with(HasModel {
-            model: Arc::from(cfg::MODEL_ENEMY_1),
+            model: enemy.model.clone(),
         })
-        .with(Critter { speed: 50.0 })
+        .with(Critter { speed: enemy.speed })
         .with(Team::Enemy)
         .with(Damageable {
-            hp: 10.0,
-            max_hp: 10.0,
-            kill_score: 1,
+            hp: enemy.hp,
+            max_hp: enemy.hp,
+            kill_score: enemy.kill_score,
         })
         .with(Velocity {
             vel: Default::default(),
         })
         .with(DamageCollider {
-            damage: 1.0,
+            damage: enemy.damage,
             affects: Team::Player,
             disposable: false,
+            status: enemy.status.clone(),
         })
         .with(Collider {
             shape: Shape::Circle,
-            scale: 12.0,
+            scale: enemy.collider_radius,
             sensor: false,
+            mass: enemy.mass,
+            is_static: false,
+            layer: layers::ENEMY,
+            mask: layers::ENEMY_MASK,
+        })
+        .with(Ai::new(&enemy.ai))
+        .maybe_with(enemy.caster.as_ref().map(new_enemy_caster))
+        .with(StatusEffects::default())
+        .maybe_with(enemy.boss.as_ref().map(|boss| Boss::new(boss, enemy.speed)))
+}
+
+fn new_enemy_caster(cfg: &EnemyCasterCfg) -> Caster {
+    let mut caster = Caster::new(&cfg.spells);
+    caster.max_mana = cfg.max_mana;
+    caster.mana = cfg.max_mana;
+    caster.mana_recharge = cfg.mana_recharge;
+    caster.casting_skill = cfg.casting_skill;
+    caster
+}
+
+pub fn new_obstacle<B: Builder>(builder: B, obstacle: &ObstacleCfg) -> B {
+    builder
+        .with(Position {
+            pos: obstacle.pos,
+            angle: obstacle.angle,
+        })
+        .with(HasModel {
+            model: obstacle.model.clone(),
+        })
+        .with(Obstacle)
+        .with(Collider {
+            shape: obstacle.shape.clone(),
+            scale: obstacle.scale,
+            is_static: true,
+            layer: layers::OBSTACLE,
+            mask: layers::OBSTACLE_MASK,
+            ..Default::default()
         })
-        .with(Ai::FollowPlayer)
 }
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use specs::Entity;
+
 use crate::cfg::Cfg;
-use crate::spell::SpellAtLevel;
+use crate::spell::{SpellAtLevel, SpellCode};
 
 pub type Radians = f32;
 pub type V2 = glam::f32::Vec2;
@@ -17,15 +19,20 @@ pub type Damage = f32;
 pub type Hp = f32;
 pub type Radius = f32;
 pub type Speed = f32;
+pub type Mass = f32;
 pub type ModelRef = &'static str;
 pub type Model = Arc<str>;
 /// How much a work a caster need to execute to be able to cast a spell
 pub type CastComplexity = f32;
 pub type SkillPoint = i32;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct DeltaTime(pub f32);
 
+/// delta time of a single tick when stepping the simulation
+pub const FIXED_DELTA_TIME: DeltaTime = DeltaTime(1.0 / 60.0);
+
 impl DeltaTime {
     pub fn as_seconds_f32(&self) -> f32 {
         self.0
@@ -46,7 +53,8 @@ impl From<f32> for DeltaTime {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct TotalTime(pub f64);
 
 impl From<f64> for TotalTime {
@@ -95,13 +103,87 @@ impl std::ops::Sub<DeltaTime> for DeltaTime {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneryParams {
     pub screen_size: V2,
     pub seed: u64,
     pub cfg: Cfg,
 }
 
+/// final result of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerDiedOutcome {
+    pub score: Score,
+    pub level: Level,
+    pub time_survived: TotalTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum GameState {
+    #[default]
+    Running,
+    Paused,
+    PlayerDead(PlayerDiedOutcome),
+}
+
+impl GameState {
+    pub fn is_running(&self) -> bool {
+        matches!(self, GameState::Running)
+    }
+}
+
+/// accumulate real time and split it in ticks of a fixed delta time
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    pub delta_time: DeltaTime,
+    /// max ticks run in a single update, the remaining time is dropped
+    pub max_ticks: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(delta_time: DeltaTime) -> Self {
+        FixedTimestep {
+            delta_time,
+            max_ticks: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    /// add the real elapsed time and return how many fixed ticks must run
+    pub fn advance(&mut self, elapsed: DeltaTime) -> u32 {
+        let step = self.delta_time.as_seconds_f32();
+        self.accumulator += elapsed.as_seconds_f32().max(0.0);
+
+        let mut ticks = 0;
+        while self.accumulator >= step && ticks < self.max_ticks {
+            self.accumulator -= step;
+            ticks += 1;
+        }
+
+        if self.accumulator >= step {
+            log::debug!(
+                "fixed timestep is behind, dropping {:.3}s",
+                self.accumulator - self.accumulator % step
+            );
+            self.accumulator %= step;
+        }
+
+        ticks
+    }
+
+    /// how far we are between the previous and the current tick, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.delta_time.as_seconds_f32()).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(FIXED_DELTA_TIME)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CastPoint {
     pub pos: V2,
@@ -115,13 +197,15 @@ pub struct CastPoint {
 //     pub chant: CastComplexity,
 // }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CasterState {
     Idle,
     Cast {
+        code: SpellCode,
         spell: SpellAtLevel,
     },
     Casting {
+        code: SpellCode,
         spell: SpellAtLevel,
         /// decrement until zero
         progress: CastComplexity,
@@ -133,6 +217,7 @@ pub enum CasterState {
 }
 
 impl CasterState {
+    #[allow(clippy::match_like_matches_macro)]
     pub fn has_casted(&self) -> bool {
         match self {
             Self::Cast { .. } => true,
@@ -147,6 +232,7 @@ impl CasterState {
         }
     }
 
+    #[allow(clippy::match_like_matches_macro)]
     pub fn is_idle(&self) -> bool {
         match self {
             CasterState::Idle => true,
@@ -161,6 +247,7 @@ impl CasterState {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for CasterState {
     fn default() -> Self {
         CasterState::Idle
@@ -194,4 +281,34 @@ impl Contacts {
     pub fn list(&self) -> &Vec<(Entity, Entity)> {
         &self.contacts
     }
+
+    /// drop the contacts of an entity deleted in the middle of the tick
+    pub fn remove_entity(&mut self, e: Entity) {
+        self.contacts.retain(|(e1, e2)| *e1 != e && *e2 != e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_timestep_accumulate() {
+        let mut ts = FixedTimestep::new(DeltaTime(0.1));
+        assert_eq!(0, ts.advance(DeltaTime(0.05)));
+        assert_abs_diff_eq!(0.5, ts.alpha(), epsilon = 0.001);
+
+        assert_eq!(2, ts.advance(DeltaTime(0.2)));
+        assert_abs_diff_eq!(0.5, ts.alpha(), epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_fixed_timestep_drop_when_behind() {
+        let mut ts = FixedTimestep::new(DeltaTime(0.1));
+        ts.max_ticks = 3;
+        assert_eq!(3, ts.advance(DeltaTime(1.05)));
+        assert_abs_diff_eq!(0.5, ts.alpha(), epsilon = 0.001);
+    }
 }
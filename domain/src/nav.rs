@@ -0,0 +1,327 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::collision::resolve_collision;
+use crate::components::{Collider, Position, Shape};
+use crate::models::{V2, V2I};
+
+/// cost of moving to a side neighbour, diagonals cost 14 to keep integer costs
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const UNREACHABLE: u32 = u32::MAX;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// walkable cells of the arena, cells are blocked when a critter at its center would touch a
+/// static collider
+#[derive(Debug, Clone)]
+pub struct NavGrid {
+    cell_size: f32,
+    width: i32,
+    height: i32,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    pub fn new(size: V2, cell_size: f32) -> Self {
+        let width = (size.x / cell_size).ceil().max(1.0) as i32;
+        let height = (size.y / cell_size).ceil().max(1.0) as i32;
+        NavGrid {
+            cell_size,
+            width,
+            height,
+            blocked: vec![false; (width * height) as usize],
+        }
+    }
+
+    /// block all cells where a critter of the clearance radius would touch the collider
+    pub fn block(&mut self, pos: &Position, col: &Collider, clearance: f32) {
+        let agent = Collider {
+            shape: Shape::Circle,
+            scale: clearance.max(0.01),
+            ..Default::default()
+        };
+
+        let reach = V2::splat(col.bounding_radius() + clearance);
+        let min = self.to_cell(pos.pos - reach);
+        let max = self.to_cell(pos.pos + reach);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = V2I::new(x, y);
+                let center = Position {
+                    pos: self.cell_center(cell),
+                    angle: 0.0,
+                };
+                if resolve_collision(&center, &agent, pos, col).is_some() {
+                    let index = self.index(cell);
+                    self.blocked[index] = true;
+                }
+            }
+        }
+    }
+
+    /// cell of the position, positions outside the grid are moved to the nearest cell
+    pub fn to_cell(&self, pos: V2) -> V2I {
+        let cell = (pos / self.cell_size).floor().as_ivec2();
+        cell.clamp(V2I::ZERO, V2I::new(self.width - 1, self.height - 1))
+    }
+
+    pub fn cell_center(&self, cell: V2I) -> V2 {
+        (cell.as_vec2() + 0.5) * self.cell_size
+    }
+
+    fn contains(&self, cell: V2I) -> bool {
+        cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.height
+    }
+
+    fn index(&self, cell: V2I) -> usize {
+        (cell.y * self.width + cell.x) as usize
+    }
+
+    /// cells outside the grid are blocked
+    pub fn is_blocked(&self, cell: V2I) -> bool {
+        !self.contains(cell) || self.blocked[self.index(cell)]
+    }
+
+    /// true when no blocked cell is crossed walking straight from one position to the other
+    pub fn has_line_of_sight(&self, from: V2, to: V2) -> bool {
+        let steps = (from.distance(to) / (self.cell_size * 0.5)).ceil() as i32;
+        (0..=steps).all(|i| {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                i as f32 / steps as f32
+            };
+            !self.is_blocked(self.to_cell(from.lerp(to, t)))
+        })
+    }
+
+    /// neighbours that can be walked to from the cell, diagonals can not cut the corner of a
+    /// blocked cell
+    fn walkable_neighbours(&self, cell: V2I) -> impl Iterator<Item = (V2I, u32)> + '_ {
+        NEIGHBOURS.iter().filter_map(move |(dx, dy)| {
+            let next = cell + V2I::new(*dx, *dy);
+            if self.is_blocked(next) {
+                return None;
+            }
+            if *dx != 0 && *dy != 0 {
+                let side_x = cell + V2I::new(*dx, 0);
+                let side_y = cell + V2I::new(0, *dy);
+                if self.is_blocked(side_x) || self.is_blocked(side_y) {
+                    return None;
+                }
+                Some((next, DIAGONAL_COST))
+            } else {
+                Some((next, STRAIGHT_COST))
+            }
+        })
+    }
+}
+
+/// cost to reach a target from every cell of a grid, shared by all critters chasing it
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    target: V2I,
+    costs: Vec<u32>,
+}
+
+impl FlowField {
+    /// dijkstra from the target cell, the target is expanded even if it is blocked
+    pub fn new(grid: &NavGrid, target: V2) -> Self {
+        let target = grid.to_cell(target);
+        let mut costs = vec![UNREACHABLE; grid.blocked.len()];
+        let mut queue = BinaryHeap::new();
+
+        costs[grid.index(target)] = 0;
+        queue.push(Reverse((0, target.x, target.y)));
+
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let cell = V2I::new(x, y);
+            if cost > costs[grid.index(cell)] {
+                continue;
+            }
+
+            for (next, step) in grid.walkable_neighbours(cell) {
+                let next_cost = cost + step;
+                let index = grid.index(next);
+                if next_cost < costs[index] {
+                    costs[index] = next_cost;
+                    queue.push(Reverse((next_cost, next.x, next.y)));
+                }
+            }
+        }
+
+        FlowField { target, costs }
+    }
+
+    pub fn target(&self) -> V2I {
+        self.target
+    }
+
+    /// direction to walk from the position, towards the center of the cheapest neighbour cell,
+    /// none when the target can not be reached
+    pub fn direction(&self, grid: &NavGrid, pos: V2) -> Option<V2> {
+        let cell = grid.to_cell(pos);
+        if cell == self.target {
+            return None;
+        }
+
+        // a critter pushed into a blocked cell walks to any reachable neighbour
+        let current = self.costs[grid.index(cell)];
+        let candidates: Vec<V2I> = if current == UNREACHABLE {
+            NEIGHBOURS
+                .iter()
+                .map(|(dx, dy)| cell + V2I::new(*dx, *dy))
+                .filter(|next| grid.contains(*next))
+                .collect()
+        } else {
+            grid.walkable_neighbours(cell)
+                .map(|(next, _)| next)
+                .collect()
+        };
+
+        let next = candidates
+            .into_iter()
+            .min_by_key(|next| self.costs[grid.index(*next)])?;
+
+        if self.costs[grid.index(next)] >= current {
+            return None;
+        }
+
+        Some((grid.cell_center(next) - pos).normalize_or_zero())
+    }
+}
+
+/// navigation of the current scenery, the grid is built once from the static colliders and the
+/// flow field follows the player
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    pub grid: Option<NavGrid>,
+    pub field: Option<FlowField>,
+}
+
+impl Navigation {
+    /// size of the grid cells, smaller than critters to pass between close obstacles
+    pub const CELL_SIZE: f32 = 16.0;
+
+    /// without a grid everything is in sight
+    pub fn has_line_of_sight(&self, from: V2, to: V2) -> bool {
+        self.grid
+            .as_ref()
+            .map(|grid| grid.has_line_of_sight(from, to))
+            .unwrap_or(true)
+    }
+
+    /// direction to walk to the target of the flow field, straight when nothing is in the way
+    pub fn chase_dir(&self, from: V2, to: V2) -> V2 {
+        let straight = (to - from).normalize_or_zero();
+        if self.has_line_of_sight(from, to) {
+            return straight;
+        }
+        match (&self.grid, &self.field) {
+            (Some(grid), Some(field)) => field.direction(grid, from).unwrap_or(straight),
+            _ => straight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_grid_with_wall() -> NavGrid {
+        // 10x10 cells with a vertical wall on x = 5, open on the last row
+        let mut grid = NavGrid::new(V2::new(100.0, 100.0), 10.0);
+        grid.block(
+            &Position {
+                pos: V2::new(55.0, 45.0),
+                angle: 0.0,
+            },
+            &Collider {
+                shape: Shape::Aabb {
+                    half_extents: V2::new(4.0, 44.0),
+                },
+                is_static: true,
+                ..Default::default()
+            },
+            1.0,
+        );
+        grid
+    }
+
+    #[test]
+    fn test_block_cells() {
+        let grid = new_grid_with_wall();
+        assert!(grid.is_blocked(V2I::new(5, 0)));
+        assert!(grid.is_blocked(V2I::new(5, 8)));
+        assert!(!grid.is_blocked(V2I::new(5, 9)));
+        assert!(!grid.is_blocked(V2I::new(4, 5)));
+        assert!(!grid.is_blocked(V2I::new(6, 5)));
+        assert!(grid.is_blocked(V2I::new(-1, 5)));
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let grid = new_grid_with_wall();
+        assert!(grid.has_line_of_sight(V2::new(15.0, 15.0), V2::new(45.0, 85.0)));
+        assert!(!grid.has_line_of_sight(V2::new(15.0, 15.0), V2::new(85.0, 15.0)));
+        assert!(grid.has_line_of_sight(V2::new(15.0, 95.0), V2::new(85.0, 95.0)));
+    }
+
+    #[test]
+    fn test_flow_field_goes_around_the_wall() {
+        let grid = new_grid_with_wall();
+        let field = FlowField::new(&grid, V2::new(85.0, 15.0));
+        assert_eq!(V2I::new(8, 1), field.target());
+
+        // walk the field from the other side of the wall
+        let mut pos = V2::new(15.0, 15.0);
+        let mut visited = vec![grid.to_cell(pos)];
+        for _ in 0..100 {
+            let dir = match field.direction(&grid, pos) {
+                Some(dir) => dir,
+                None => break,
+            };
+            pos += dir * 5.0;
+            assert!(!grid.is_blocked(grid.to_cell(pos)));
+            visited.push(grid.to_cell(pos));
+        }
+
+        assert_eq!(field.target(), grid.to_cell(pos));
+        // the only way is the open row
+        assert!(visited.contains(&V2I::new(5, 9)));
+    }
+
+    #[test]
+    fn test_flow_field_unreachable_target() {
+        let mut grid = NavGrid::new(V2::new(100.0, 100.0), 10.0);
+        // close the left side
+        grid.block(
+            &Position {
+                pos: V2::new(55.0, 50.0),
+                angle: 0.0,
+            },
+            &Collider {
+                shape: Shape::Aabb {
+                    half_extents: V2::new(4.0, 50.0),
+                },
+                is_static: true,
+                ..Default::default()
+            },
+            1.0,
+        );
+
+        let field = FlowField::new(&grid, V2::new(85.0, 15.0));
+        assert_eq!(None, field.direction(&grid, V2::new(15.0, 15.0)));
+        assert!(field.direction(&grid, V2::new(75.0, 75.0)).is_some());
+    }
+}
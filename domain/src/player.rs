@@ -1,36 +1,52 @@
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs_derive::Component;
 
-use crate::caster::Caster;
-use crate::components::{Critter, Damageable, Position, Velocity};
+use crate::caster::{CastError, Caster};
+use crate::components::{Critter, Damageable, Frame, Position, Velocity};
+use crate::events::{Events, Feedback, GameEvent};
 use crate::math;
 use crate::models::*;
-use crate::spell::SpellCode;
+use crate::spell::{SpellCode, SpellSlot};
+use crate::status::StatusEffects;
 
 pub fn level_from_score(score: Score) -> Level {
     f32::sqrt(score as f32).floor() as Level
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PlayerUpgradeRequest {
     Health,
     Mana,
     Recharge,
     SkillCasting,
-    Firebold,
+    /// increase the level of a spell in the spell book
+    Spell(SpellCode),
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpgradeError {
+    NoSkillPoints,
+    UnknownSpell(SpellCode),
+    SpellMaxLevel(SpellCode),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PlayerInput {
     pub input_dir: V2,
     pub mouse_pos: V2,
     pub cast: Option<SpellCode>,
+    /// change the selected spell, ignored if there is no spell on the slot
+    pub select_slot: Option<SpellSlot>,
+    /// cast the spell on the selected slot
+    pub cast_selected: bool,
     pub upgrade: Option<PlayerUpgradeRequest>,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub input: PlayerInput,
+    selected_slot: SpellSlot,
     score: Score,
     level: Level,
     free_skill_points: SkillPoint,
@@ -40,6 +56,7 @@ impl Default for Player {
     fn default() -> Self {
         Player {
             input: Default::default(),
+            selected_slot: 0,
             score: 0,
             level: 0,
             free_skill_points: 4,
@@ -48,7 +65,15 @@ impl Default for Player {
 }
 
 impl Player {
-    pub fn update_score(&mut self, score: Score) {
+    pub fn new(free_skill_points: SkillPoint) -> Self {
+        Player {
+            free_skill_points,
+            ..Default::default()
+        }
+    }
+
+    /// return the new level when the player level up
+    pub fn update_score(&mut self, score: Score) -> Option<Level> {
         self.score += score;
         let new_level = level_from_score(self.score);
         if new_level != self.level {
@@ -59,6 +84,9 @@ impl Player {
                 self.level,
                 self.free_skill_points
             );
+            Some(new_level)
+        } else {
+            None
         }
     }
 
@@ -78,50 +106,52 @@ impl Player {
     pub fn free_skill_points(&self) -> SkillPoint {
         self.free_skill_points
     }
+    pub fn selected_slot(&self) -> SpellSlot {
+        self.selected_slot
+    }
 }
 
 pub struct PlayerSystem;
 
 impl<'a> System<'a> for PlayerSystem {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, Player>,
         WriteStorage<'a, Velocity>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Critter>,
         WriteStorage<'a, Caster>,
-        WriteStorage<'a, Damageable>,
-        ReadExpect<'a, SceneryParams>,
+        ReadStorage<'a, StatusEffects>,
+        WriteExpect<'a, Events>,
     );
 
     fn run(
         &mut self,
         (
+            entities,
             mut players,
             mut velocities,
             mut positions,
-            mut critters,
+            critters,
             mut caster,
-            mut damageables,
-            scenery_params,
+            statuses,
+            mut events,
         ): Self::SystemData,
     ) {
-        for (pla, vel, pos, cri, cas, dam) in (
+        for (e, pla, vel, pos, cri, cas) in (
+            &entities,
             &mut players,
             &mut velocities,
             &mut positions,
             &critters,
             &mut caster,
-            &mut damageables,
         )
             .join()
         {
-            // level up
-            if let Some(upgrade) = pla.input.upgrade.clone() {
-                _ = player_upgrade(pla, dam, cas, upgrade);
-            }
+            let stunned = statuses.get(e).map(|st| st.is_stunned()).unwrap_or(false);
 
             // move
-            if pla.input.input_dir.length_squared() <= 0.1 {
+            if stunned || pla.input.input_dir.length_squared() <= 0.1 {
                 vel.vel = V2::ZERO;
             } else {
                 vel.vel = pla.input.input_dir.normalize() * cri.speed;
@@ -131,29 +161,118 @@ impl<'a> System<'a> for PlayerSystem {
             let mouse_delta = pla.input.mouse_pos - pos.pos;
             pos.angle = math::angle_of(mouse_delta);
 
+            // spell selection
+            if let Some(slot) = pla.input.select_slot.take() {
+                if cas.spell_book.get_slot(slot).is_some() {
+                    pla.selected_slot = slot;
+                } else {
+                    log::warn!("invalid spell slot {}", slot);
+                }
+            }
+
+            if pla.input.cast_selected {
+                pla.input.cast_selected = false;
+                pla.input.cast = cas
+                    .spell_book
+                    .get_slot(pla.selected_slot)
+                    .map(|e| e.spell.spell_code.clone());
+            }
+
             // casting
             if let Some(code) = pla.input.cast.take() {
-                let rs = cas.cast(code);
-                if rs.is_ok() {
-                    log::debug!("player starting to cast");
+                let result = if stunned {
+                    Err(CastError::Stunned)
+                } else {
+                    cas.cast(code.clone())
+                };
+                match result {
+                    Ok(mana) => {
+                        log::debug!("player starting to cast");
+                        events.game_events.push(GameEvent::CastStarted {
+                            caster: e,
+                            spell: code,
+                            mana,
+                        });
+                        if cas.is_mana_depleted() {
+                            events
+                                .game_events
+                                .push(GameEvent::ManaDepleted { caster: e });
+                        }
+                    }
+                    Err(err) => {
+                        log::debug!("player fail to cast {:?}", err);
+                        events.feedback.push(Feedback::CastFailed(err));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// apply upgrade requests, it also runs while the game is paused
+pub struct PlayerUpgradeSystem;
+
+impl<'a> System<'a> for PlayerUpgradeSystem {
+    type SystemData = (
+        WriteStorage<'a, Player>,
+        WriteStorage<'a, Caster>,
+        WriteStorage<'a, Damageable>,
+        WriteExpect<'a, Events>,
+    );
+
+    fn run(&mut self, (mut players, mut casters, mut damageables, mut events): Self::SystemData) {
+        for (pla, cas, dam) in (&mut players, &mut casters, &mut damageables).join() {
+            if let Some(upgrade) = pla.input.upgrade.take() {
+                if let Err(err) = player_upgrade(pla, dam, cas, upgrade) {
+                    log::debug!("player upgrade rejected {:?}", err);
+                    events.feedback.push(Feedback::UpgradeFailed(err));
                 }
             }
         }
     }
 }
 
+/// change the game state when the player dies
+pub struct PlayerDeathSystem;
+
+impl<'a> System<'a> for PlayerDeathSystem {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Damageable>,
+        ReadExpect<'a, Frame>,
+        WriteExpect<'a, GameState>,
+    );
+
+    fn run(&mut self, (players, damageables, frame, mut state): Self::SystemData) {
+        // ticks also run while paused by Api::step
+        if matches!(*state, GameState::PlayerDead(_)) {
+            return;
+        }
+
+        for (pla, dam) in (&players, &damageables).join() {
+            if dam.hp < 0.0 {
+                let outcome = PlayerDiedOutcome {
+                    score: pla.score(),
+                    level: pla.level(),
+                    time_survived: frame.total_time,
+                };
+                log::info!("player died {:?}", outcome);
+                *state = GameState::PlayerDead(outcome);
+            }
+        }
+    }
+}
+
 fn player_upgrade(
     player: &mut Player,
     damageable: &mut Damageable,
     caster: &mut Caster,
     request: PlayerUpgradeRequest,
-) -> Result<(), ()> {
+) -> Result<(), UpgradeError> {
     if player.free_skill_points <= 0 {
-        return Err(());
+        return Err(UpgradeError::NoSkillPoints);
     }
 
-    player.free_skill_points -= 1;
-
     match request {
         PlayerUpgradeRequest::Health => {
             damageable.max_hp += 1.0;
@@ -167,15 +286,92 @@ fn player_upgrade(
         PlayerUpgradeRequest::SkillCasting => {
             caster.casting_skill += 0.1;
         }
-        PlayerUpgradeRequest::Firebold => {}
+        PlayerUpgradeRequest::Spell(code) => {
+            let entry = caster
+                .spell_book
+                .find_mut(&code)
+                .ok_or_else(|| UpgradeError::UnknownSpell(code.clone()))?;
+            if entry.is_max_level() {
+                return Err(UpgradeError::SpellMaxLevel(code));
+            }
+            entry.level += 1;
+            log::debug!("spell {} upgraded to level {}", code, entry.level);
+        }
     }
 
+    player.free_skill_points -= 1;
+
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::cfg::Cfg;
+
+    fn new_player() -> (Player, Damageable, Caster) {
+        let cfg = Cfg::default();
+        let damageable = Damageable {
+            hp: 10.0,
+            max_hp: 10.0,
+            kill_score: 0,
+        };
+        (Player::new(10), damageable, Caster::new(&cfg.spells))
+    }
+
+    #[test]
+    fn test_upgrade_spell() {
+        let (mut pla, mut dam, mut cas) = new_player();
+        let code = SpellCode::from("firebold");
+        let max_level = cas.spell_book.find(&code).unwrap().max_level();
+        assert_eq!(2, max_level);
+
+        for level in 1..=max_level {
+            let rs = player_upgrade(
+                &mut pla,
+                &mut dam,
+                &mut cas,
+                PlayerUpgradeRequest::Spell(code.clone()),
+            );
+            assert_eq!(Ok(()), rs);
+            assert_eq!(level, cas.spell_book.find(&code).unwrap().level);
+        }
+        assert_eq!(10 - max_level, pla.free_skill_points());
+
+        // already at max level, skill point is not spent
+        let rs = player_upgrade(
+            &mut pla,
+            &mut dam,
+            &mut cas,
+            PlayerUpgradeRequest::Spell(code.clone()),
+        );
+        assert_eq!(Err(UpgradeError::SpellMaxLevel(code.clone())), rs);
+        assert_eq!(max_level, cas.spell_book.find(&code).unwrap().level);
+        assert_eq!(10 - max_level, pla.free_skill_points());
+    }
+
+    #[test]
+    fn test_upgrade_unknown_spell() {
+        let (mut pla, mut dam, mut cas) = new_player();
+        let code = SpellCode::from("unknown");
+        let rs = player_upgrade(
+            &mut pla,
+            &mut dam,
+            &mut cas,
+            PlayerUpgradeRequest::Spell(code.clone()),
+        );
+        assert_eq!(Err(UpgradeError::UnknownSpell(code)), rs);
+        assert_eq!(10, pla.free_skill_points());
+    }
+
+    #[test]
+    fn test_upgrade_without_skill_points() {
+        let (_, mut dam, mut cas) = new_player();
+        let mut pla = Player::new(0);
+        let rs = player_upgrade(&mut pla, &mut dam, &mut cas, PlayerUpgradeRequest::Health);
+        assert_eq!(Err(UpgradeError::NoSkillPoints), rs);
+        assert_eq!(10.0, dam.max_hp);
+    }
 
     #[test]
     fn test_level() {
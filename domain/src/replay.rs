@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cfg::Cfg;
+use crate::error::GameError;
+use crate::models::*;
+use crate::player::PlayerInput;
+
+/// a single simulation update with the input the player had on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub input: PlayerInput,
+    /// None when the update happened while paused, only upgrades are processed
+    pub delta_time: Option<DeltaTime>,
+}
+
+/// everything required to reproduce a session from the start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub screen_size: V2,
+    pub seed: u64,
+    pub cfg: Cfg,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn new(params: &SceneryParams) -> Self {
+        Replay {
+            screen_size: params.screen_size,
+            seed: params.seed,
+            cfg: params.cfg.clone(),
+            frames: vec![],
+        }
+    }
+
+    pub fn get_scenery_params(&self) -> SceneryParams {
+        SceneryParams {
+            screen_size: self.screen_size,
+            seed: self.seed,
+            cfg: self.cfg.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, GameError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| GameError::Msg(format!("fail to read {:?}: {}", path, e)))?;
+        Replay::from_ron(&content)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        let content = self.to_ron()?;
+        std::fs::write(path, content)
+            .map_err(|e| GameError::Msg(format!("fail to write {:?}: {}", path, e)))
+    }
+
+    pub fn from_ron(content: &str) -> Result<Replay, GameError> {
+        let replay: Replay = ron::from_str(content)
+            .map_err(|e| GameError::Msg(format!("fail to parse replay: {}", e)))?;
+        replay.cfg.validate()?;
+        Ok(replay)
+    }
+
+    pub fn to_ron(&self) -> Result<String, GameError> {
+        ron::ser::to_string(self)
+            .map_err(|e| GameError::Msg(format!("fail to serialize replay: {}", e)))
+    }
+}
@@ -0,0 +1,118 @@
+use specs::prelude::*;
+
+use crate::brain::PlayerBrain;
+use crate::cfg::Cfg;
+use crate::components::Frame;
+use crate::error::GameError;
+use crate::events::GameEvent;
+use crate::models::*;
+use crate::player::Player;
+use crate::Api;
+
+/// run a scenery without rendering
+#[derive(Debug, Clone)]
+pub struct SimulationParams {
+    pub cfg: Cfg,
+    pub seed: u64,
+    pub screen_size: V2,
+    /// stop after this time even if the player is alive
+    pub duration: DeltaTime,
+    pub delta_time: DeltaTime,
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        SimulationParams {
+            cfg: Cfg::default(),
+            seed: 0,
+            screen_size: V2::new(1920.0, 1080.0),
+            duration: DeltaTime(60.0),
+            delta_time: FIXED_DELTA_TIME,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunStats {
+    pub seed: u64,
+    pub time_survived: TotalTime,
+    pub died: bool,
+    pub kills: u32,
+    pub score: Score,
+    pub level: Level,
+    pub mana_spent: Mana,
+    pub damage_taken: Damage,
+}
+
+/// run the scenery until the player dies or the duration expires, the brain decides the player
+/// input before each tick
+pub fn run_simulation(
+    params: &SimulationParams,
+    brain: &mut dyn PlayerBrain,
+) -> Result<RunStats, GameError> {
+    let mut api = Api::default();
+    api.start_scenery(SceneryParams {
+        screen_size: params.screen_size,
+        seed: params.seed,
+        cfg: params.cfg.clone(),
+    })?;
+
+    let player = {
+        let entities = api.world.entities();
+        let players = api.world.read_storage::<Player>();
+        (&entities, &players)
+            .join()
+            .next()
+            .map(|(e, _)| e)
+            .ok_or(GameError::Str("player not found"))?
+    };
+
+    let mut stats = RunStats {
+        seed: params.seed,
+        ..Default::default()
+    };
+
+    let end_time = TotalTime::default().add(params.duration);
+    loop {
+        let input = brain.next_input(&api.world);
+        api.set_player_input(input)?;
+        api.update(params.delta_time)?;
+
+        let events = api.take_events();
+        for event in &events.game_events {
+            match event {
+                GameEvent::Killed { killer, .. } if *killer == player => stats.kills += 1,
+                GameEvent::Damaged { target, amount, .. } if *target == player => {
+                    stats.damage_taken += amount
+                }
+                GameEvent::CastStarted { caster, mana, .. } if *caster == player => {
+                    stats.mana_spent += mana
+                }
+                _ => {}
+            }
+        }
+
+        let total_time = api.world.read_resource::<Frame>().total_time;
+        match api.get_game_state() {
+            GameState::PlayerDead(outcome) => {
+                stats.died = true;
+                stats.time_survived = outcome.time_survived;
+                break;
+            }
+            _ if total_time.is_after(end_time) => {
+                stats.time_survived = total_time;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let players = api.world.read_storage::<Player>();
+    let pla = players
+        .get(player)
+        .ok_or(GameError::Str("player not found"))?;
+    stats.score = pla.score();
+    stats.level = pla.level();
+
+    Ok(stats)
+}
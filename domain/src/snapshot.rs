@@ -0,0 +1,211 @@
+use std::convert::Infallible;
+use std::path::Path;
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use specs::saveload::{
+    DeserializeComponents, MarkerAllocator, SerializeComponents, SimpleMarker,
+    SimpleMarkerAllocator,
+};
+
+use crate::caster::Caster;
+use crate::components::*;
+use crate::error::GameError;
+use crate::models::*;
+use crate::player::Player;
+use crate::status::StatusEffects;
+use crate::systems::EnemySpawnerSystem;
+
+/// tag of the markers used to identify entities between saves and loads
+pub struct Saved;
+
+pub type SavedMarker = SimpleMarker<Saved>;
+pub type SavedMarkerAllocator = SimpleMarkerAllocator<Saved>;
+
+/// full state of a running scenery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub params: SceneryParams,
+    pub frame: Frame,
+    pub game_state: GameState,
+    /// full state of the world rng
+    pub rng: ChaCha12Rng,
+    /// progress of the waves
+    pub spawner: EnemySpawnerSystem,
+    /// serialized entities, one list per group of components
+    pub components: Vec<serde_json::Value>,
+}
+
+impl Snapshot {
+    pub fn load(path: &Path) -> Result<Snapshot, GameError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| GameError::Msg(format!("fail to read {:?}: {}", path, e)))?;
+        Snapshot::from_json(&content)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        let content = self.to_json()?;
+        std::fs::write(path, content)
+            .map_err(|e| GameError::Msg(format!("fail to write {:?}: {}", path, e)))
+    }
+
+    pub fn from_json(content: &str) -> Result<Snapshot, GameError> {
+        serde_json::from_str(content)
+            .map_err(|e| GameError::Msg(format!("fail to parse snapshot: {}", e)))
+    }
+
+    pub fn to_json(&self) -> Result<String, GameError> {
+        serde_json::to_string(self)
+            .map_err(|e| GameError::Msg(format!("fail to serialize snapshot: {}", e)))
+    }
+}
+
+/// remove all markers, markers only exist while saving or loading so the world is the same
+/// with or without saves
+pub fn clear_markers(world: &World) {
+    world.write_storage::<SavedMarker>().clear();
+    *world.write_resource::<SavedMarkerAllocator>() = SavedMarkerAllocator::new();
+}
+
+/// mark all entities and serialize their components, the markers are removed after
+pub fn save_components(world: &World) -> Result<Vec<serde_json::Value>, GameError> {
+    {
+        let entities = world.entities();
+        let owners = world.read_storage::<Owner>();
+        if (&owners)
+            .join()
+            .any(|owner| !entities.is_alive(owner.entity))
+        {
+            return Err(GameError::Str("owner references a removed entity"));
+        }
+    }
+
+    clear_markers(world);
+    {
+        let entities = world.entities();
+        let mut markers = world.write_storage::<SavedMarker>();
+        let mut allocator = world.write_resource::<SavedMarkerAllocator>();
+        for e in entities.join() {
+            allocator.mark(e, &mut markers);
+        }
+    }
+
+    let result = serialize_components(world);
+    clear_markers(world);
+    result
+}
+
+fn serialize_components(world: &World) -> Result<Vec<serde_json::Value>, GameError> {
+    fn to_value(
+        result: Result<serde_json::Value, serde_json::Error>,
+    ) -> Result<serde_json::Value, GameError> {
+        result.map_err(|e| GameError::Msg(format!("fail to serialize components: {}", e)))
+    }
+
+    let entities = world.entities();
+    let markers = world.read_storage::<SavedMarker>();
+
+    let objects = SerializeComponents::<Infallible, SavedMarker>::serialize(
+        &(
+            &world.read_storage::<Position>(),
+            &world.read_storage::<PrevPosition>(),
+            &world.read_storage::<Velocity>(),
+            &world.read_storage::<HasModel>(),
+            &world.read_storage::<Critter>(),
+            &world.read_storage::<Player>(),
+            &world.read_storage::<Caster>(),
+            &world.read_storage::<Damageable>(),
+            &world.read_storage::<Collider>(),
+            &world.read_storage::<Team>(),
+            &world.read_storage::<Ai>(),
+            &world.read_storage::<Obstacle>(),
+            &world.read_storage::<Boss>(),
+            &world.read_storage::<StatusEffects>(),
+        ),
+        &entities,
+        &markers,
+        serde_json::value::Serializer,
+    );
+
+    let effects = SerializeComponents::<Infallible, SavedMarker>::serialize(
+        &(
+            &world.read_storage::<DamageCollider>(),
+            &world.read_storage::<Deadline>(),
+            &world.read_storage::<Explosive>(),
+            &world.read_storage::<Area>(),
+            &world.read_storage::<Owner>(),
+        ),
+        &entities,
+        &markers,
+        serde_json::value::Serializer,
+    );
+
+    Ok(vec![to_value(objects)?, to_value(effects)?])
+}
+
+/// create the entities of serialized components, the world is expected to be empty
+pub fn load_components(
+    world: &mut World,
+    components: &[serde_json::Value],
+) -> Result<(), GameError> {
+    if components.len() != 2 {
+        return Err(GameError::Msg(format!(
+            "invalid snapshot, expected 2 component groups, found {}",
+            components.len()
+        )));
+    }
+
+    fn check(result: Result<(), serde_json::Error>) -> Result<(), GameError> {
+        result.map_err(|e| GameError::Msg(format!("fail to deserialize components: {}", e)))
+    }
+
+    world.insert(SavedMarkerAllocator::new());
+
+    let entities = world.entities();
+    let mut markers = world.write_storage::<SavedMarker>();
+    let mut allocator = world.write_resource::<SavedMarkerAllocator>();
+
+    check(
+        DeserializeComponents::<Infallible, SavedMarker>::deserialize(
+            &mut (
+                world.write_storage::<Position>(),
+                world.write_storage::<PrevPosition>(),
+                world.write_storage::<Velocity>(),
+                world.write_storage::<HasModel>(),
+                world.write_storage::<Critter>(),
+                world.write_storage::<Player>(),
+                world.write_storage::<Caster>(),
+                world.write_storage::<Damageable>(),
+                world.write_storage::<Collider>(),
+                world.write_storage::<Team>(),
+                world.write_storage::<Ai>(),
+                world.write_storage::<Obstacle>(),
+                world.write_storage::<Boss>(),
+                world.write_storage::<StatusEffects>(),
+            ),
+            &entities,
+            &mut markers,
+            &mut allocator,
+            components[0].clone(),
+        ),
+    )?;
+
+    check(
+        DeserializeComponents::<Infallible, SavedMarker>::deserialize(
+            &mut (
+                world.write_storage::<DamageCollider>(),
+                world.write_storage::<Deadline>(),
+                world.write_storage::<Explosive>(),
+                world.write_storage::<Area>(),
+                world.write_storage::<Owner>(),
+            ),
+            &entities,
+            &mut markers,
+            &mut allocator,
+            components[1].clone(),
+        ),
+    )?;
+
+    Ok(())
+}
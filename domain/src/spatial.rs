@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::models::{V2, V2I};
+
+/// uniform grid used as collision broad phase, objects are indexed by the cells their bounding
+/// box touches
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<V2I, Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// remove all objects and their cells, so only cells touched since the last clear are kept
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn to_cell(&self, pos: V2) -> V2I {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    /// index an object by its bounding box
+    pub fn insert(&mut self, index: usize, min: V2, max: V2) {
+        let min = self.to_cell(min);
+        let max = self.to_cell(max);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.cells.entry(V2I::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// all pairs of objects sharing at least one cell, as (lower index, higher index) sorted and
+    /// without duplicates
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for cell in self.cells.values() {
+            for (i, a) in cell.iter().enumerate() {
+                for b in &cell[i + 1..] {
+                    pairs.push(((*a).min(*b), (*a).max(*b)));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_candidate_pairs() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, V2::new(0.0, 0.0), V2::new(5.0, 5.0));
+        // shares two cells with 2, but the pair is returned once
+        grid.insert(1, V2::new(8.0, 8.0), V2::new(12.0, 12.0));
+        grid.insert(2, V2::new(11.0, 8.0), V2::new(15.0, 12.0));
+        // negative coordinates, far from everybody
+        grid.insert(3, V2::new(-25.0, -25.0), V2::new(-21.0, -21.0));
+
+        assert_eq!(vec![(0, 1), (1, 2)], grid.candidate_pairs());
+
+        grid.clear();
+        assert!(grid.cells.is_empty());
+        assert!(grid.candidate_pairs().is_empty());
+    }
+}
@@ -1,48 +1,130 @@
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
+
 use super::models::*;
+use super::status::StatusEffectCfg;
 
 pub type SpellLevel = i32;
 pub type SpellCode = Arc<str>;
+/// index of the spell in the spell book
+pub type SpellSlot = usize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpellBookEntry {
     pub level: Level,
     pub spell: Spell,
 }
 
 /// list of available spells on the level know of each spell
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SpellBook {
     pub spells: Vec<SpellBookEntry>,
 }
 
+impl SpellBook {
+    pub fn get_slot(&self, slot: SpellSlot) -> Option<&SpellBookEntry> {
+        self.spells.get(slot)
+    }
+
+    pub fn find(&self, code: &SpellCode) -> Option<&SpellBookEntry> {
+        self.spells.iter().find(|e| &e.spell.spell_code == code)
+    }
+
+    pub fn find_mut(&mut self, code: &SpellCode) -> Option<&mut SpellBookEntry> {
+        self.spells.iter_mut().find(|e| &e.spell.spell_code == code)
+    }
+}
+
+impl SpellBookEntry {
+    pub fn current(&self) -> Option<&SpellAtLevel> {
+        self.spell.per_level.get(self.level as usize)
+    }
+
+    pub fn max_level(&self) -> Level {
+        self.spell.per_level.len() as Level - 1
+    }
+
+    pub fn is_max_level(&self) -> bool {
+        self.level >= self.max_level()
+    }
+}
+
 /// list spells per level
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Spell {
     pub spell_code: SpellCode,
     pub per_level: Vec<SpellAtLevel>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SpellEffect {
     Projectile {
         damage: Damage,
         speed: Speed,
         ttl: DeltaTime,
+        /// applied to the hit target
+        #[serde(default)]
+        status: Option<StatusEffectCfg>,
     },
     ExplosiveProject {
         damage: Damage,
         speed: Speed,
         radius: Radius,
+        ttl: DeltaTime,
+        /// applied to all targets in the explosion
+        #[serde(default)]
+        status: Option<StatusEffectCfg>,
     },
     Area {
         damage: Damage,
         radius: Radius,
+        target: AreaTarget,
+        /// zero for a single hit, otherwise the area persist and the damage is applied per second
+        duration: DeltaTime,
+        /// applied to all targets in the area, persistent areas refresh it every tick
+        #[serde(default)]
+        status: Option<StatusEffectCfg>,
     },
 }
 
-#[derive(Debug, Clone)]
+impl SpellEffect {
+    /// max distance from the caster where the spell can hit, used by the ai to decide when to
+    /// cast
+    pub fn reach(&self) -> f32 {
+        match self {
+            SpellEffect::Projectile { speed, ttl, .. }
+            | SpellEffect::ExplosiveProject { speed, ttl, .. } => speed * ttl.as_seconds_f32(),
+            SpellEffect::Area {
+                radius,
+                target: AreaTarget::Caster,
+                ..
+            } => *radius,
+            SpellEffect::Area {
+                target: AreaTarget::Aim,
+                ..
+            } => f32::INFINITY,
+        }
+    }
+
+    pub fn status(&self) -> Option<&StatusEffectCfg> {
+        match self {
+            SpellEffect::Projectile { status, .. }
+            | SpellEffect::ExplosiveProject { status, .. }
+            | SpellEffect::Area { status, .. } => status.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AreaTarget {
+    /// centered on the caster
+    Caster,
+    /// centered on the point the caster is aiming, the mouse position for the player
+    Aim,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpellAtLevel {
     pub mana_cost: Mana,
     pub cast_complexity: CastComplexity,
@@ -0,0 +1,249 @@
+use std::convert::Infallible;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use specs::saveload::{ConvertSaveload, Marker};
+use specs::Entity;
+use specs_derive::Component;
+
+use crate::models::*;
+
+/// time between damage ticks of burn and poison
+pub const DAMAGE_TICK: DeltaTime = DeltaTime(0.5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusKind {
+    /// damage over time
+    Burn,
+    /// speed reduced by the slow fraction
+    Slow,
+    /// can not move
+    Freeze,
+    /// damage over time
+    Poison,
+    /// can not act, no ai, no player movement and casts are interrupted
+    Stun,
+}
+
+/// status applied on hit by spells and enemy attacks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffectCfg {
+    pub kind: StatusKind,
+    pub duration: DeltaTime,
+    /// used by burn and poison
+    #[serde(default)]
+    pub damage_per_second: Damage,
+    /// fraction of the speed removed by slow
+    #[serde(default)]
+    pub slow: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveStatus {
+    pub cfg: StatusEffectCfg,
+    pub until: TotalTime,
+    pub next_tick: TotalTime,
+    /// credited with kills by damage over time
+    pub source: Option<Entity>,
+}
+
+/// timed effects on a critter, expired by Frame::total_time
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<ActiveStatus>,
+    /// speed of the critter before slow or freeze, restored when they expire
+    pub base_speed: Option<Speed>,
+}
+
+/// serialized ActiveStatus, the source is saved by its marker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveStatusData<M> {
+    pub cfg: StatusEffectCfg,
+    pub until: TotalTime,
+    pub next_tick: TotalTime,
+    pub source: Option<M>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffectsData<M> {
+    pub effects: Vec<ActiveStatusData<M>>,
+    pub base_speed: Option<Speed>,
+}
+
+impl<M> ConvertSaveload<M> for StatusEffects
+where
+    M: Marker + Serialize + DeserializeOwned,
+{
+    type Data = StatusEffectsData<M>;
+    type Error = Infallible;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let effects = self
+            .effects
+            .iter()
+            .map(|e| ActiveStatusData {
+                cfg: e.cfg.clone(),
+                until: e.until,
+                next_tick: e.next_tick,
+                source: e.source.and_then(&mut ids),
+            })
+            .collect();
+        Ok(StatusEffectsData {
+            effects,
+            base_speed: self.base_speed,
+        })
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        let effects = data
+            .effects
+            .into_iter()
+            .map(|e| ActiveStatus {
+                cfg: e.cfg,
+                until: e.until,
+                next_tick: e.next_tick,
+                source: e.source.and_then(&mut ids),
+            })
+            .collect();
+        Ok(StatusEffects {
+            effects,
+            base_speed: data.base_speed,
+        })
+    }
+}
+
+impl StatusEffects {
+    /// a single effect per kind, applying it again refreshes the values and extends the duration
+    pub fn apply(&mut self, cfg: &StatusEffectCfg, source: Entity, now: TotalTime) {
+        let until = now.add(cfg.duration);
+        match self.effects.iter_mut().find(|e| e.cfg.kind == cfg.kind) {
+            Some(active) => {
+                active.cfg = cfg.clone();
+                active.source = Some(source);
+                if until.is_after(active.until) {
+                    active.until = until;
+                }
+            }
+            None => self.effects.push(ActiveStatus {
+                cfg: cfg.clone(),
+                until,
+                next_tick: now.add(DAMAGE_TICK),
+                source: Some(source),
+            }),
+        }
+    }
+
+    /// remove the effects that ended at the time
+    pub fn expire(&mut self, now: TotalTime) {
+        self.effects.retain(|e| !now.is_after(e.until));
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|e| e.cfg.kind == kind)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(StatusKind::Stun)
+    }
+
+    /// multiplier of the critter speed, zero when frozen
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| match e.cfg.kind {
+                StatusKind::Freeze => 0.0,
+                StatusKind::Slow => (1.0 - e.cfg.slow).clamp(0.0, 1.0),
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    /// damage of burn and poison ticks due at the time, with the source of each one
+    pub fn take_damage_ticks(&mut self, now: TotalTime) -> Vec<(Option<Entity>, Damage)> {
+        let mut ticks = vec![];
+        for e in &mut self.effects {
+            if !matches!(e.cfg.kind, StatusKind::Burn | StatusKind::Poison) {
+                continue;
+            }
+
+            while now.is_after(e.next_tick) && e.until.is_after(e.next_tick) {
+                ticks.push((
+                    e.source,
+                    e.cfg.damage_per_second * DAMAGE_TICK.as_seconds_f32(),
+                ));
+                e.next_tick = e.next_tick.add(DAMAGE_TICK);
+            }
+        }
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_source() -> Entity {
+        let mut world = World::new();
+        world.create_entity().build()
+    }
+
+    fn cfg(kind: StatusKind, duration: f32) -> StatusEffectCfg {
+        StatusEffectCfg {
+            kind,
+            duration: DeltaTime(duration),
+            damage_per_second: 2.0,
+            slow: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_apply_refresh_and_expire() {
+        let source = new_source();
+        let mut status = StatusEffects::default();
+        status.apply(&cfg(StatusKind::Slow, 2.0), source, TotalTime::from(0.0));
+        status.apply(&cfg(StatusKind::Freeze, 1.0), source, TotalTime::from(0.0));
+        assert_eq!(0.0, status.speed_multiplier());
+
+        // refreshing does not add a second slow
+        status.apply(&cfg(StatusKind::Slow, 2.0), source, TotalTime::from(1.0));
+        assert_eq!(2, status.effects.len());
+
+        status.expire(TotalTime::from(1.5));
+        assert!(!status.has(StatusKind::Freeze));
+        assert_eq!(0.5, status.speed_multiplier());
+
+        status.expire(TotalTime::from(3.0));
+        assert!(status.effects.is_empty());
+        assert_eq!(1.0, status.speed_multiplier());
+    }
+
+    #[test]
+    fn test_damage_ticks() {
+        let source = new_source();
+        let mut status = StatusEffects::default();
+        status.apply(&cfg(StatusKind::Burn, 2.0), source, TotalTime::from(0.0));
+        status.apply(&cfg(StatusKind::Stun, 2.0), source, TotalTime::from(0.0));
+        assert!(status.is_stunned());
+
+        assert!(status.take_damage_ticks(TotalTime::from(0.4)).is_empty());
+        assert_eq!(
+            vec![(Some(source), 1.0), (Some(source), 1.0)],
+            status.take_damage_ticks(TotalTime::from(1.0))
+        );
+
+        // the whole duration deals damage_per_second * duration
+        let rest: Damage = status
+            .take_damage_ticks(TotalTime::from(10.0))
+            .iter()
+            .map(|(_, amount)| amount)
+            .sum();
+        assert_eq!(2.0, rest);
+    }
+}
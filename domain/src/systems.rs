@@ -1,16 +1,21 @@
-use std::collections::HashSet;
-
 use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
 use crate::caster::Caster;
+use crate::cfg::{BossAttackCfg, EnemyCode};
+use crate::collision::resolve_collision;
 use crate::damage;
-use crate::events::Events;
-use crate::models::{Contacts, DeltaTime, SceneryParams, TotalTime, V2};
+use crate::events::{Events, Explosion, GameEvent};
+use crate::models::{CasterState, Contacts, SceneryParams, TotalTime, V2};
+use crate::nav::{FlowField, NavGrid, Navigation};
 use crate::player::Player;
-use crate::spell::SpellEffect;
-use crate::unwrap_or_return;
+use crate::spatial::SpatialGrid;
+use crate::spell::{AreaTarget, SpellEffect};
+use crate::status::StatusEffects;
 use crate::{loader, math};
+use crate::{unwrap_or_continue, unwrap_or_return};
 
 use super::components::*;
 
@@ -20,21 +25,92 @@ impl<'a> System<'a> for DeadlineSystem {
     type SystemData = (
         Entities<'a>,
         ReadStorage<'a, Deadline>,
+        ReadStorage<'a, Explosive>,
+        ReadStorage<'a, DamageCollider>,
         ReadExpect<'a, Frame>,
         WriteExpect<'a, Events>,
     );
 
-    fn run(&mut self, (entities, poisons, frame, mut events): Self::SystemData) {
-        for (poi, e) in (&poisons, &entities).join() {
+    fn run(
+        &mut self,
+        (entities, poisons, explosives, damage_colliders, frame, mut events): Self::SystemData,
+    ) {
+        // explosives are detonated by the ExplosiveSystem
+        for (poi, e, _) in (&poisons, &entities, !&explosives).join() {
             if poi.deadline.is_before(frame.total_time) {
                 log::debug!("deleting {:?} by deadline", e);
                 events.removed.push(e);
+                if damage_colliders.contains(e) {
+                    events
+                        .game_events
+                        .push(GameEvent::ProjectileExpired { entity: e });
+                }
                 entities.delete(e).expect("fail to delete entity");
             }
         }
     }
 }
 
+/// drop the references to entities removed on the tick, runs after maintain so owners and status
+/// sources never point to removed entities
+pub struct DeadReferencesSystem;
+
+impl<'a> System<'a> for DeadReferencesSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Owner>,
+        WriteStorage<'a, StatusEffects>,
+    );
+
+    fn run(&mut self, (entities, mut owners, mut statuses): Self::SystemData) {
+        let orphans: Vec<Entity> = (&entities, &owners)
+            .join()
+            .filter(|(_, owner)| !entities.is_alive(owner.entity))
+            .map(|(e, _)| e)
+            .collect();
+        for e in orphans {
+            log::trace!("dropping owner of {:?}, owner was removed", e);
+            owners.remove(e);
+        }
+
+        for st in (&mut statuses).join() {
+            for active in &mut st.effects {
+                if active
+                    .source
+                    .is_some_and(|source| !entities.is_alive(source))
+                {
+                    active.source = None;
+                }
+            }
+        }
+    }
+}
+
+/// keep the position of the previous tick of every object
+pub struct PrevPositionSystem;
+
+impl<'a> System<'a> for PrevPositionSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, PrevPosition>,
+    );
+
+    fn run(&mut self, (entities, positions, mut prev_positions): Self::SystemData) {
+        for (e, pos) in (&entities, &positions).join() {
+            prev_positions
+                .insert(
+                    e,
+                    PrevPosition {
+                        pos: pos.pos,
+                        angle: pos.angle,
+                    },
+                )
+                .expect("fail to insert prev position");
+        }
+    }
+}
+
 pub struct VelocitySystem;
 
 impl<'a> System<'a> for VelocitySystem {
@@ -44,6 +120,7 @@ impl<'a> System<'a> for VelocitySystem {
         ReadExpect<'a, Frame>,
     );
 
+    #[allow(clippy::assign_op_pattern)]
     fn run(&mut self, (velocities, mut positions, frame): Self::SystemData) {
         for (vel, pos) in (&velocities, &mut positions).join() {
             pos.pos = pos.pos + vel.vel * frame.delta_time.as_seconds_f32();
@@ -57,6 +134,8 @@ impl<'a> System<'a> for CasterSystem {
     type SystemData = (
         WriteStorage<'a, Caster>,
         ReadStorage<'a, Position>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Team>,
         ReadExpect<'a, Frame>,
         Entities<'a>,
         Read<'a, LazyUpdate>,
@@ -65,7 +144,7 @@ impl<'a> System<'a> for CasterSystem {
 
     fn run(
         &mut self,
-        (mut casters, positions, frame, mut entities, updates, mut events): Self::SystemData,
+        (mut casters, positions, players, teams, frame, entities, updates, mut events): Self::SystemData,
     ) {
         for (caster_entity, cas, pos) in (&entities, &mut casters, &positions)
             .join()
@@ -73,37 +152,156 @@ impl<'a> System<'a> for CasterSystem {
         {
             cas.update(frame.delta_time);
 
-            if let Some(spell) = cas.has_cast() {
-                let casting_pos = pos.pos + V2::from_angle(pos.angle) * 50.0;
+            if let Some((code, spell)) = cas.has_cast() {
+                events.game_events.push(GameEvent::SpellCast {
+                    caster: caster_entity,
+                    spell: code.clone(),
+                });
+
+                // casters without team are on the player side
+                let affects = teams
+                    .get(caster_entity)
+                    .map(|team| team.opponent())
+                    .unwrap_or(Team::Enemy);
+
+                let dir = cas
+                    .target
+                    .map(|target| (target - pos.pos).normalize_or_zero())
+                    .filter(|dir| *dir != V2::ZERO)
+                    .unwrap_or_else(|| V2::from_angle(pos.angle));
+                let angle = math::angle_of(dir);
+                let casting_pos = pos.pos + dir * 50.0;
 
-                match spell.effect {
-                    SpellEffect::Projectile { damage, speed, ttl } => {
+                match spell.effect.clone() {
+                    SpellEffect::Projectile {
+                        damage,
+                        speed,
+                        ttl,
+                        status,
+                    } => {
                         let missile_entity = loader::create_magic_missile(
-                            updates.create_entity(&mut entities),
+                            updates.create_entity(&entities),
                             Some(caster_entity),
                             Position {
                                 pos: casting_pos,
-                                angle: pos.angle,
+                                angle,
                             },
-                            V2::from_angle(pos.angle),
-                            damage,
+                            dir,
                             speed,
+                            DamageCollider {
+                                damage,
+                                affects,
+                                disposable: true,
+                                status,
+                            },
                             frame.total_time.add(ttl),
                         )
                         .build();
                         log::debug!("casting spell {:?}", missile_entity);
                         events.added.push(missile_entity);
                     }
-                    _ => todo!(),
+                    SpellEffect::ExplosiveProject {
+                        damage,
+                        speed,
+                        radius,
+                        ttl,
+                        status,
+                    } => {
+                        let missile_entity = loader::create_explosive_missile(
+                            updates.create_entity(&entities),
+                            Some(caster_entity),
+                            Position {
+                                pos: casting_pos,
+                                angle,
+                            },
+                            dir,
+                            speed,
+                            Explosive {
+                                damage,
+                                radius,
+                                affects,
+                                status,
+                            },
+                            frame.total_time.add(ttl),
+                        )
+                        .build();
+                        log::debug!("casting explosive spell {:?}", missile_entity);
+                        events.added.push(missile_entity);
+                    }
+                    SpellEffect::Area {
+                        damage,
+                        radius,
+                        target,
+                        duration,
+                        status,
+                    } => {
+                        let center = match target {
+                            AreaTarget::Caster => pos.pos,
+                            AreaTarget::Aim => players
+                                .get(caster_entity)
+                                .map(|p| p.input.mouse_pos)
+                                .or(cas.target)
+                                .unwrap_or(pos.pos),
+                        };
+                        let instant = duration.as_seconds_f32() <= 0.0;
+                        let area_entity = loader::create_area(
+                            updates.create_entity(&entities),
+                            Some(caster_entity),
+                            center,
+                            Area {
+                                damage,
+                                radius,
+                                affects,
+                                instant,
+                                status,
+                            },
+                            if instant {
+                                None
+                            } else {
+                                Some(frame.total_time.add(duration))
+                            },
+                        )
+                        .build();
+                        log::debug!("casting area spell {:?} at {:?}", area_entity, center);
+                        // instant areas are only visible by the explosion
+                        if !instant {
+                            events.added.push(area_entity);
+                        }
+                    }
                 }
             }
         }
     }
 }
 
-#[derive(Debug, Default)]
+/// progress of the current wave
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WaveState {
+    /// waiting to start the next wave
+    Break { until: TotalTime },
+    /// spawning the remaining enemies of the wave
+    Spawning {
+        pending: Vec<EnemyCode>,
+        next_spawn: TotalTime,
+    },
+    /// everything spawned, waiting for the enemies to be killed
+    Fighting,
+}
+
+impl Default for WaveState {
+    fn default() -> Self {
+        WaveState::Break {
+            until: TotalTime::default(),
+        }
+    }
+}
+
+/// wave director, spawns the waves of the cfg at random screen edges
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct EnemySpawnerSystem {
-    next_spawn: TotalTime,
+    /// current wave starting on 1, zero before the first wave
+    pub wave: u32,
+    pub state: WaveState,
 }
 
 impl<'a> System<'a> for EnemySpawnerSystem {
@@ -113,80 +311,415 @@ impl<'a> System<'a> for EnemySpawnerSystem {
         WriteExpect<'a, Events>,
         ReadExpect<'a, Frame>,
         ReadExpect<'a, SceneryParams>,
-        WriteExpect<'a, StdRng>,
+        WriteExpect<'a, ChaCha12Rng>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Critter>,
+        ReadStorage<'a, Team>,
     );
 
     fn run(
         &mut self,
-        (mut entities, updates, mut events, frame, params, mut rng): Self::SystemData,
+        (entities, updates, mut events, frame, params, mut rng, players, critters, teams): Self::SystemData,
     ) {
-        if frame.total_time.is_before(self.next_spawn) {
+        let now = frame.total_time;
+        let spawn_cfg = &params.cfg.spawn;
+
+        // the first enemy of the wave spawns on the tick it starts
+        if let WaveState::Break { until } = &self.state {
+            if !now.is_after(*until) {
+                return;
+            }
+
+            let wave = unwrap_or_return!(spawn_cfg.get_wave(self.wave));
+            self.wave += 1;
+
+            let mut pending: Vec<EnemyCode> = wave
+                .enemies
+                .iter()
+                .flat_map(|entry| (0..entry.count).map(|_| entry.enemy.clone()))
+                .collect();
+            pending.shuffle(&mut *rng);
+
+            if let Some(boss) = &wave.boss {
+                spawn_enemy(&entities, &updates, &mut events, &params, &mut rng, boss);
+            }
+
+            log::debug!("starting wave {} with {} enemies", self.wave, pending.len());
+            events.game_events.push(GameEvent::WaveStarted {
+                wave: self.wave,
+                boss: wave.boss.is_some(),
+            });
+
+            self.state = WaveState::Spawning {
+                pending,
+                next_spawn: now,
+            };
+        }
+
+        match &mut self.state {
+            WaveState::Break { .. } => {}
+
+            WaveState::Spawning {
+                pending,
+                next_spawn,
+            } => {
+                if !now.is_after(*next_spawn) {
+                    return;
+                }
+
+                let enemy = match pending.pop() {
+                    Some(enemy) => enemy,
+                    None => {
+                        self.state = WaveState::Fighting;
+                        return;
+                    }
+                };
+                spawn_enemy(&entities, &updates, &mut events, &params, &mut rng, &enemy);
+
+                let wave = unwrap_or_return!(spawn_cfg.get_wave(self.wave.saturating_sub(1)));
+                let level = (&players).join().next().map(|p| p.level()).unwrap_or(0);
+                *next_spawn = now.add(spawn_cfg.get_interval(&wave, now, level));
+            }
+
+            WaveState::Fighting => {
+                let enemies_alive = (&critters, &teams)
+                    .join()
+                    .any(|(_, team)| *team == Team::Enemy);
+                if enemies_alive {
+                    return;
+                }
+
+                log::debug!("wave {} cleared", self.wave);
+                events
+                    .game_events
+                    .push(GameEvent::WaveCleared { wave: self.wave });
+                self.state = WaveState::Break {
+                    until: now.add(spawn_cfg.break_time),
+                };
+            }
+        }
+    }
+}
+
+/// spawn the enemy at a random screen edge facing the screen
+fn spawn_enemy(
+    entities: &Entities,
+    updates: &LazyUpdate,
+    events: &mut Events,
+    params: &SceneryParams,
+    rng: &mut ChaCha12Rng,
+    code: &EnemyCode,
+) {
+    let enemy = match params.cfg.find_enemy(code) {
+        Some(enemy) => enemy,
+        None => {
+            log::warn!("unknown enemy {} on spawn", code);
             return;
         }
-        self.next_spawn = frame.total_time.add(DeltaTime(3.0));
-
-        let side = rng.gen_range(0..4);
-        let position = match side {
-            // top
-            0 => Position {
-                pos: V2::new(rng.gen_range(0..params.screen_size.x as i32) as f32, 0.0),
-                angle: std::f32::consts::PI * 0.5,
-            },
-            // down
-            1 => Position {
-                pos: V2::new(
-                    rng.gen_range(0..params.screen_size.x as i32) as f32,
-                    params.screen_size.y,
-                ),
-                angle: std::f32::consts::PI * -0.5,
-            },
-            // left
-            2 => Position {
-                pos: V2::new(0.0, rng.gen_range(0..params.screen_size.y as i32) as f32),
-                angle: 0.0,
-            },
-            // right
-            3 => Position {
-                pos: V2::new(
-                    params.screen_size.x,
-                    rng.gen_range(0..params.screen_size.y as i32) as f32,
-                ),
-                angle: std::f32::consts::PI,
-            },
-            _ => panic!("non expected random number"),
-        };
+    };
 
-        let critter = loader::new_critter(updates.create_entity(&mut entities), position).build();
-        events.added.push(critter);
+    let side = rng.gen_range(0..4);
+    let position = match side {
+        // top
+        0 => Position {
+            pos: V2::new(rng.gen_range(0..params.screen_size.x as i32) as f32, 0.0),
+            angle: std::f32::consts::PI * 0.5,
+        },
+        // down
+        1 => Position {
+            pos: V2::new(
+                rng.gen_range(0..params.screen_size.x as i32) as f32,
+                params.screen_size.y,
+            ),
+            angle: std::f32::consts::PI * -0.5,
+        },
+        // left
+        2 => Position {
+            pos: V2::new(0.0, rng.gen_range(0..params.screen_size.y as i32) as f32),
+            angle: 0.0,
+        },
+        // right
+        3 => Position {
+            pos: V2::new(
+                params.screen_size.x,
+                rng.gen_range(0..params.screen_size.y as i32) as f32,
+            ),
+            angle: std::f32::consts::PI,
+        },
+        _ => panic!("non expected random number"),
+    };
 
-        log::debug!(
-            "spawning critter {:?}, next spawn on {:?}",
-            critter,
-            self.next_spawn
-        );
+    let critter = loader::new_critter(updates.create_entity(entities), position, enemy).build();
+    events.added.push(critter);
+    log::debug!("spawning critter {:?} {}", critter, code);
+}
+
+/// build the navigation grid from the static colliders and keep the flow field pointing to the
+/// player
+pub struct NavigationSystem {}
+
+impl<'a> System<'a> for NavigationSystem {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Collider>,
+        ReadExpect<'a, SceneryParams>,
+        WriteExpect<'a, Navigation>,
+    );
+
+    fn run(&mut self, (players, positions, colliders, params, mut navigation): Self::SystemData) {
+        if navigation.grid.is_none() {
+            // cells are blocked for the biggest enemy, smaller ones keep some distance
+            let clearance = params
+                .cfg
+                .enemies
+                .iter()
+                .map(|e| e.collider_radius)
+                .fold(0.0, f32::max);
+
+            let mut grid = NavGrid::new(params.screen_size, Navigation::CELL_SIZE);
+            for (pos, col) in (&positions, &colliders).join() {
+                if col.is_static {
+                    grid.block(pos, col, clearance);
+                }
+            }
+
+            navigation.grid = Some(grid);
+            navigation.field = None;
+        }
+
+        let (_, player_pos) = unwrap_or_return!((&players, &positions).join().next());
+
+        let navigation = &mut *navigation;
+        let grid = navigation.grid.as_ref().unwrap();
+        let target = grid.to_cell(player_pos.pos);
+        if navigation.field.as_ref().map(|f| f.target()) != Some(target) {
+            log::trace!("updating flow field to {:?}", target);
+            navigation.field = Some(FlowField::new(grid, player_pos.pos));
+        }
+    }
+}
+
+/// move bosses through their phases and run the attack of the current phase
+pub struct BossSystem {}
+
+impl<'a> System<'a> for BossSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, LazyUpdate>,
+        WriteExpect<'a, Events>,
+        ReadExpect<'a, Frame>,
+        ReadExpect<'a, SceneryParams>,
+        WriteStorage<'a, Boss>,
+        WriteStorage<'a, Ai>,
+        WriteStorage<'a, Critter>,
+        ReadStorage<'a, Damageable>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Team>,
+        WriteStorage<'a, StatusEffects>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            updates,
+            mut events,
+            frame,
+            params,
+            mut bosses,
+            mut ais,
+            mut critters,
+            damageables,
+            positions,
+            colliders,
+            teams,
+            mut statuses,
+        ): Self::SystemData,
+    ) {
+        let now = frame.total_time;
+
+        for (entity, boss, ai, critter, dam, pos) in (
+            &entities,
+            &mut bosses,
+            &mut ais,
+            &mut critters,
+            &damageables,
+            &positions,
+        )
+            .join()
+        {
+            // phases only move forward, healing does not go back
+            if let Some(index) = boss
+                .phase_for(dam.hp / dam.max_hp)
+                .filter(|index| Some(*index) > boss.phase)
+            {
+                let phase = &boss.cfg.phases[index];
+                if let Some(ai_cfg) = &phase.ai {
+                    *ai = Ai::new(ai_cfg);
+                }
+                // a slowed boss gets the speed of the phase when the slow expires
+                let speed = boss.base_speed * phase.speed_multiplier;
+                match statuses
+                    .get_mut(entity)
+                    .and_then(|st| st.base_speed.as_mut())
+                {
+                    Some(base_speed) => *base_speed = speed,
+                    None => critter.speed = speed,
+                }
+                boss.next_attack = now.add(phase.attack_interval);
+                boss.phase = Some(index);
+
+                log::debug!("{:?} boss entering phase {}", entity, index);
+                events.game_events.push(GameEvent::BossPhaseChanged {
+                    boss: entity,
+                    phase: index as u32,
+                });
+            }
+
+            let phase = unwrap_or_continue!(boss.current_phase());
+            let attack = unwrap_or_continue!(phase.attack.clone());
+            if !now.is_after(boss.next_attack) {
+                continue;
+            }
+            boss.next_attack = now.add(phase.attack_interval);
+
+            let radius = colliders
+                .get(entity)
+                .map(|col| col.bounding_radius())
+                .unwrap_or(0.0);
+
+            match attack {
+                BossAttackCfg::Summon { enemy, count } => {
+                    let enemy = unwrap_or_continue!(params.cfg.find_enemy(&enemy));
+                    let distance = radius + enemy.collider_radius + 10.0;
+                    for i in 0..count {
+                        let dir = V2::from_angle(std::f32::consts::TAU * i as f32 / count as f32);
+                        let critter = loader::new_critter(
+                            updates.create_entity(&entities),
+                            Position {
+                                pos: pos.pos + dir * distance,
+                                angle: math::angle_of(dir),
+                            },
+                            enemy,
+                        )
+                        .build();
+                        events.added.push(critter);
+                    }
+                }
+
+                BossAttackCfg::RadialBurst {
+                    projectiles,
+                    damage,
+                    speed,
+                    ttl,
+                } => {
+                    let affects = teams
+                        .get(entity)
+                        .map(|team| team.opponent())
+                        .unwrap_or(Team::Player);
+                    for i in 0..projectiles {
+                        let dir =
+                            V2::from_angle(std::f32::consts::TAU * i as f32 / projectiles as f32);
+                        let missile = loader::create_magic_missile(
+                            updates.create_entity(&entities),
+                            Some(entity),
+                            Position {
+                                pos: pos.pos + dir * (radius + 5.0),
+                                angle: math::angle_of(dir),
+                            },
+                            dir,
+                            speed,
+                            DamageCollider {
+                                damage,
+                                affects,
+                                disposable: true,
+                                status: None,
+                            },
+                            now.add(ttl),
+                        )
+                        .build();
+                        events.added.push(missile);
+                    }
+                }
+            }
+        }
     }
 }
 
 pub struct AiSystem {}
 
+impl AiSystem {
+    /// ranged critters move only when the distance is off by more than this fraction
+    const RANGED_TOLERANCE: f32 = 0.1;
+
+    fn face(pos: &mut Position, dir: V2) {
+        if dir != V2::ZERO {
+            pos.angle = math::angle_of(dir);
+        }
+    }
+}
+
 impl<'a> System<'a> for AiSystem {
     type SystemData = (
+        Entities<'a>,
         ReadStorage<'a, Player>,
-        ReadStorage<'a, Ai>,
+        WriteStorage<'a, Ai>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, Velocity>,
-        WriteStorage<'a, Critter>,
+        ReadStorage<'a, Critter>,
+        WriteStorage<'a, Caster>,
+        ReadStorage<'a, StatusEffects>,
+        ReadExpect<'a, Navigation>,
+        ReadExpect<'a, Frame>,
     );
 
     fn run(
         &mut self,
-        (players, ais, mut positions, mut velocities, mut critters): Self::SystemData,
+        (
+            entities,
+            players,
+            mut ais,
+            mut positions,
+            mut velocities,
+            critters,
+            mut casters,
+            statuses,
+            navigation,
+            frame,
+        ): Self::SystemData,
     ) {
         // find player position
         let (_, player_pos) = unwrap_or_return!((&players, &positions).join().next());
         let player_pos = player_pos.to_owned();
 
-        for (ai, pos, vel, cri) in (&ais, &mut positions, &mut velocities, &mut critters).join() {
+        // swarmers flock with the swarmers of the previous positions
+        let swarmers: Vec<(Entity, V2, V2)> = (&entities, &ais, &positions, &velocities)
+            .join()
+            .filter(|(_, ai, _, _)| matches!(ai, Ai::Swarmer(_)))
+            .map(|(e, _, pos, vel)| (e, pos.pos, vel.vel))
+            .collect();
+
+        for (e, ai, pos, vel, cri) in (
+            &entities,
+            &mut ais,
+            &mut positions,
+            &mut velocities,
+            &critters,
+        )
+            .join()
+        {
+            // stunned critters neither move nor cast
+            if statuses.get(e).map(|st| st.is_stunned()).unwrap_or(false) {
+                vel.vel = V2::ZERO;
+                continue;
+            }
+
+            let to_player = player_pos.pos - pos.pos;
+            let distance = to_player.length();
+            let aim = to_player.normalize_or_zero();
+
             match ai {
                 Ai::FollowPlayer => {
                     let dir = (player_pos.pos - pos.pos).normalize();
@@ -194,6 +727,115 @@ impl<'a> System<'a> for AiSystem {
                     let target_vel = cri.speed * dir;
                     vel.vel = target_vel;
                 }
+                Ai::FollowFlowField => {
+                    let dir = navigation.chase_dir(pos.pos, player_pos.pos);
+                    Self::face(pos, dir);
+                    vel.vel = cri.speed * dir;
+                }
+                Ai::Ranged(cfg) => {
+                    let dir = if distance > cfg.preferred_distance * (1.0 + Self::RANGED_TOLERANCE)
+                    {
+                        navigation.chase_dir(pos.pos, player_pos.pos)
+                    } else if distance < cfg.preferred_distance * (1.0 - Self::RANGED_TOLERANCE) {
+                        -aim
+                    } else {
+                        V2::ZERO
+                    };
+                    vel.vel = cri.speed * dir;
+                    Self::face(pos, aim);
+                }
+                Ai::Charger { cfg, state } => {
+                    let now = frame.total_time;
+                    let (next_state, dir, speed) = match state.clone() {
+                        ChargeState::Approach => {
+                            let in_sight = navigation.has_line_of_sight(pos.pos, player_pos.pos);
+                            if in_sight && distance <= cfg.charge_distance {
+                                let until = now.add(cfg.wind_up);
+                                (ChargeState::WindUp { until, dir: aim }, aim, 0.0)
+                            } else {
+                                let dir = navigation.chase_dir(pos.pos, player_pos.pos);
+                                (ChargeState::Approach, dir, cri.speed)
+                            }
+                        }
+                        ChargeState::WindUp { until, dir } if now.is_after(until) => {
+                            let until = now.add(cfg.dash_duration);
+                            (ChargeState::Dash { until, dir }, dir, cfg.dash_speed)
+                        }
+                        ChargeState::Dash { until, dir } if now.is_after(until) => {
+                            let until = now.add(cfg.recover);
+                            (ChargeState::Recover { until }, dir, 0.0)
+                        }
+                        ChargeState::Recover { until } if now.is_after(until) => {
+                            (ChargeState::Approach, aim, 0.0)
+                        }
+                        ChargeState::WindUp { dir, .. } => (state.clone(), dir, 0.0),
+                        ChargeState::Dash { dir, .. } => (state.clone(), dir, cfg.dash_speed),
+                        ChargeState::Recover { .. } => (state.clone(), aim, 0.0),
+                    };
+
+                    if next_state != *state {
+                        log::trace!("{:?} charge state {:?}", e, next_state);
+                    }
+                    *state = next_state;
+                    Self::face(pos, dir);
+                    vel.vel = dir * speed;
+                }
+                Ai::Swarmer(cfg) => {
+                    let mut separation = V2::ZERO;
+                    let mut center = V2::ZERO;
+                    let mut heading = V2::ZERO;
+                    let mut count = 0;
+                    for (other, other_pos, other_vel) in &swarmers {
+                        let offset = pos.pos - *other_pos;
+                        let other_distance = offset.length();
+                        if *other == e || other_distance > cfg.neighbour_radius {
+                            continue;
+                        }
+                        // closer neighbours push harder
+                        separation += offset.normalize_or_zero()
+                            * (1.0 - other_distance / cfg.neighbour_radius);
+                        center += *other_pos;
+                        heading += other_vel.normalize_or_zero();
+                        count += 1;
+                    }
+
+                    let mut dir = navigation.chase_dir(pos.pos, player_pos.pos);
+                    if count > 0 {
+                        let cohesion = (center / count as f32 - pos.pos).normalize_or_zero();
+                        let alignment = (heading / count as f32).normalize_or_zero();
+                        dir += separation * cfg.separation
+                            + cohesion * cfg.cohesion
+                            + alignment * cfg.alignment;
+                    }
+                    let dir = dir.normalize_or_zero();
+
+                    Self::face(pos, dir);
+                    vel.vel = cri.speed * dir;
+                }
+            }
+
+            // cast the first spell that can reach the player
+            let cas = unwrap_or_continue!(casters.get_mut(e));
+            if !navigation.has_line_of_sight(pos.pos, player_pos.pos) {
+                continue;
+            }
+
+            // keep aiming at the player while casting
+            cas.target = Some(player_pos.pos);
+
+            let code = cas
+                .spell_book
+                .spells
+                .iter()
+                .filter_map(|entry| entry.current().map(|spell| (entry, spell)))
+                .find(|(_, spell)| cas.can_cast(spell) && distance <= spell.effect.reach())
+                .map(|(entry, _)| entry.spell.spell_code.clone());
+
+            if let Some(code) = code {
+                match cas.cast(code) {
+                    Ok(_) => log::debug!("{:?} starting to cast", e),
+                    Err(err) => log::warn!("{:?} fail to cast {:?}", e, err),
+                }
             }
         }
     }
@@ -201,42 +843,66 @@ impl<'a> System<'a> for AiSystem {
 
 pub struct ColliderSystem {}
 
+impl ColliderSystem {
+    /// size of the broad phase cells, bigger than most critters so they touch few cells
+    pub const CELL_SIZE: f32 = 64.0;
+}
+
 impl<'a> System<'a> for ColliderSystem {
     type SystemData = (
         Entities<'a>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Collider>,
+        ReadStorage<'a, Owner>,
         Write<'a, Contacts>,
     );
 
-    fn run(&mut self, (entities, mut positions, colliders, mut contacts): Self::SystemData) {
+    fn run(
+        &mut self,
+        (entities, mut positions, colliders, owners, mut contacts): Self::SystemData,
+    ) {
         contacts.clear();
 
+        let bodies: Vec<(Entity, Position, &Collider)> = (&entities, &positions, &colliders)
+            .join()
+            .map(|(e, pos, col)| (e, pos.clone(), col))
+            .collect();
+
+        let mut grid = SpatialGrid::new(Self::CELL_SIZE);
+        for (i, (_, pos, col)) in bodies.iter().enumerate() {
+            let radius = V2::splat(col.bounding_radius());
+            grid.insert(i, pos.pos - radius, pos.pos + radius);
+        }
+
+        // pairs are sorted by the join order, so contacts are in the same order of a full
+        // pairwise check
         let mut impulses = vec![];
-        let mut checked = HashSet::new();
+        for (i, j) in grid.candidate_pairs() {
+            let (e1, pos1, col1) = &bodies[i];
+            let (e2, pos2, col2) = &bodies[j];
 
-        for (e1, pos1, col1) in (&entities, &positions, &colliders).join() {
-            for (e2, pos2, col2) in (&entities, &positions, &colliders).join() {
-                if e1 == e2 {
-                    continue;
-                }
+            if !col1.interacts_with(col2) {
+                continue;
+            }
 
-                if checked.contains(&(e1, e2)) {
-                    continue;
-                }
+            // nothing collides with its own owner, like a caster and its projectiles
+            let is_owner = |e: &Entity, owner: &Entity| {
+                owners.get(*e).map(|o| o.entity == *owner).unwrap_or(false)
+            };
+            if is_owner(e1, e2) || is_owner(e2, e1) {
+                continue;
+            }
 
-                if let Some((v1, v2)) = resolve_collision(pos1.pos, col1, pos2.pos, col2) {
-                    let is_sensor = col1.sensor || col2.sensor;
-                    contacts.push(e1, e2, is_sensor);
+            if let Some((v1, v2)) = resolve_collision(pos1, col1, pos2, col2) {
+                let is_sensor = col1.sensor || col2.sensor;
+                contacts.push(*e1, *e2, is_sensor);
 
-                    if !is_sensor {
-                        impulses.push((e1, v1));
-                        impulses.push((e2, v2));
-                    }
+                // split the penetration by inverse mass, static bodies never move
+                let total = col1.inverse_mass() + col2.inverse_mass();
+                if !is_sensor && total > 0.0 {
+                    impulses.push((*e1, v1 * (col1.inverse_mass() / total)));
+                    impulses.push((*e2, v2 * (col2.inverse_mass() / total)));
                 }
-
-                checked.insert((e1, e2));
-                checked.insert((e2, e1));
             }
         }
 
@@ -248,53 +914,298 @@ impl<'a> System<'a> for ColliderSystem {
     }
 }
 
-fn resolve_collision(pos1: V2, col1: &Collider, pos2: V2, col2: &Collider) -> Option<(V2, V2)> {
-    match (&col1.shape, &col2.shape) {
-        (Shape::Circle, Shape::Circle) => {
-            let v = pos2 - pos1;
-            let distance = v.length();
-            let impact_distance = col1.scale + col2.scale - distance;
-            if impact_distance > 0.0 {
-                let v = v.normalize();
-                Some((v * -impact_distance, v * impact_distance))
-            } else {
-                None
-            }
-        } // _ => panic!("unsupported shapes"),
-    }
-}
-
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod test {
     use approx::assert_abs_diff_eq;
-    use glam::Vec2;
 
     use super::*;
 
     #[test]
-    fn test_resolve_collision() {
-        let contact = resolve_collision(
-            Vec2::ZERO,
-            &Collider {
-                shape: Shape::Circle,
-                scale: 10.0,
-                sensor: false,
-            },
-            V2::new(12.0, 5.0),
-            &Collider {
-                shape: Shape::Circle,
-                scale: 5.0,
-                sensor: false,
-            },
+    fn test_collider_system_same_contacts_as_pairwise() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Collider>();
+        world.register::<Owner>();
+        world.insert(Contacts::default());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for i in 0..300 {
+            world
+                .create_entity()
+                .with(Position {
+                    pos: V2::new(rng.gen_range(-500.0..500.0), rng.gen_range(-500.0..500.0)),
+                    angle: 0.0,
+                })
+                .with(Collider {
+                    shape: Shape::Circle,
+                    // some colliders are bigger than the grid cells
+                    scale: if i % 50 == 0 {
+                        100.0
+                    } else {
+                        rng.gen_range(2.0..20.0)
+                    },
+                    sensor: i % 3 == 0,
+                    ..Default::default()
+                })
+                .build();
+        }
+
+        let expected: Vec<(Entity, Entity)> = {
+            let entities = world.entities();
+            let positions = world.read_storage::<Position>();
+            let colliders = world.read_storage::<Collider>();
+            let bodies: Vec<_> = (&entities, &positions, &colliders).join().collect();
+            let mut pairs = vec![];
+            for (i, (e1, pos1, col1)) in bodies.iter().enumerate() {
+                for (e2, pos2, col2) in &bodies[i + 1..] {
+                    if resolve_collision(pos1, col1, pos2, col2).is_some() {
+                        pairs.push((*e1, *e2));
+                    }
+                }
+            }
+            pairs
+        };
+        assert!(!expected.is_empty());
+
+        let mut system = ColliderSystem {};
+        system.run_now(&world);
+
+        assert_eq!(&expected, world.read_resource::<Contacts>().list());
+    }
+
+    fn add_body(world: &mut World, x: f32, collider: Collider) -> Entity {
+        world
+            .create_entity()
+            .with(Position {
+                pos: V2::new(x, 0.0),
+                angle: 0.0,
+            })
+            .with(collider)
+            .build()
+    }
+
+    fn get_x(world: &World, e: Entity) -> f32 {
+        world.read_storage::<Position>().get(e).unwrap().pos.x
+    }
+
+    #[test]
+    fn test_collider_system_layers_and_owner() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Collider>();
+        world.register::<Owner>();
+        world.insert(Contacts::default());
+
+        let body = |layer: CollisionMask, mask: CollisionMask| Collider {
+            scale: 5.0,
+            sensor: true,
+            layer,
+            mask,
+            ..Default::default()
+        };
+
+        // all bodies overlap each other
+        let player = add_body(&mut world, 0.0, body(layers::PLAYER, layers::PLAYER_MASK));
+        let enemy = add_body(&mut world, 1.0, body(layers::ENEMY, layers::ENEMY_MASK));
+        let missile = add_body(
+            &mut world,
+            2.0,
+            body(layers::PROJECTILE, layers::PROJECTILE_MASK),
+        );
+        let missile2 = add_body(
+            &mut world,
+            3.0,
+            body(layers::PROJECTILE, layers::PROJECTILE_MASK),
+        );
+        let pickup = add_body(&mut world, 4.0, body(layers::PICKUP, layers::PICKUP_MASK));
+        let obstacle = add_body(
+            &mut world,
+            5.0,
+            body(layers::OBSTACLE, layers::OBSTACLE_MASK),
+        );
+        {
+            let mut owners = world.write_storage::<Owner>();
+            owners.insert(missile, Owner { entity: player }).unwrap();
+            owners.insert(missile2, Owner { entity: player }).unwrap();
+        }
+
+        let mut system = ColliderSystem {};
+        system.run_now(&world);
+
+        let expected = vec![
+            (player, enemy),
+            (player, pickup),
+            (player, obstacle),
+            (enemy, missile),
+            (enemy, missile2),
+            (enemy, obstacle),
+            (missile, obstacle),
+            (missile2, obstacle),
+        ];
+        assert_eq!(&expected, world.read_resource::<Contacts>().list());
+    }
+
+    #[test]
+    fn test_collider_system_split_by_mass() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Collider>();
+        world.register::<Owner>();
+        world.insert(Contacts::default());
+
+        let circle = |mass: f32, is_static: bool| Collider {
+            scale: 5.0,
+            mass,
+            is_static,
+            ..Default::default()
+        };
+
+        // penetration of 4, the light body moves 3 and the heavy 1
+        let light = add_body(&mut world, 0.0, circle(1.0, false));
+        let heavy = add_body(&mut world, 6.0, circle(3.0, false));
+        // static wall far from the others, penetration of 2
+        let wall = add_body(&mut world, 100.0, circle(1.0, true));
+        let pushed = add_body(&mut world, 108.0, circle(1.0, false));
+        // two static bodies only report the contact
+        let wall2 = add_body(&mut world, 200.0, circle(1.0, true));
+        let wall3 = add_body(&mut world, 205.0, circle(1.0, true));
+
+        let mut system = ColliderSystem {};
+        system.run_now(&world);
+
+        assert_abs_diff_eq!(-3.0, get_x(&world, light), epsilon = 0.001);
+        assert_abs_diff_eq!(7.0, get_x(&world, heavy), epsilon = 0.001);
+        assert_abs_diff_eq!(100.0, get_x(&world, wall));
+        assert_abs_diff_eq!(110.0, get_x(&world, pushed), epsilon = 0.001);
+        assert_abs_diff_eq!(200.0, get_x(&world, wall2));
+        assert_abs_diff_eq!(205.0, get_x(&world, wall3));
+        assert_eq!(3, world.read_resource::<Contacts>().list().len());
+    }
+
+    #[test]
+    fn test_arena_system_drops_contacts_of_removed_projectiles() {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Collider>();
+        world.register::<Owner>();
+        world.register::<Critter>();
+        world.register::<Velocity>();
+        world.register::<DamageCollider>();
+        world.register::<Explosive>();
+        world.insert(Contacts::default());
+        world.insert(Events::default());
+        world.insert(SceneryParams {
+            screen_size: V2::new(100.0, 100.0),
+            seed: 0,
+            cfg: Default::default(),
+        });
+
+        let body = || Collider {
+            scale: 5.0,
+            sensor: true,
+            ..Default::default()
+        };
+
+        // the enemy touches the arena border and the missile already left it
+        let enemy = add_body(&mut world, 5.0, body());
+        let missile = add_body(&mut world, -2.0, body());
+        world
+            .write_storage::<Critter>()
+            .insert(enemy, Critter { speed: 1.0 })
+            .unwrap();
+        world
+            .write_storage::<Velocity>()
+            .insert(missile, Velocity::default())
+            .unwrap();
+        world
+            .write_storage::<DamageCollider>()
+            .insert(
+                missile,
+                DamageCollider {
+                    damage: 1.0,
+                    affects: Team::Enemy,
+                    disposable: true,
+                    status: None,
+                },
+            )
+            .unwrap();
+
+        let mut system = ColliderSystem {};
+        system.run_now(&world);
+        assert_eq!(
+            &vec![(enemy, missile)],
+            world.read_resource::<Contacts>().list()
         );
 
-        assert!(contact.is_some());
-        let contact = contact.unwrap();
+        let mut system = ArenaSystem {};
+        system.run_now(&world);
 
-        assert_abs_diff_eq!(-1.846154, contact.0.x);
-        assert_abs_diff_eq!(-0.7692308, contact.0.y);
-        assert_abs_diff_eq!(1.846154, contact.1.x);
-        assert_abs_diff_eq!(0.7692308, contact.1.y);
+        assert!(world.read_resource::<Contacts>().list().is_empty());
+        assert_eq!(vec![missile], world.read_resource::<Events>().removed);
+    }
+}
+
+/// keep critters inside the arena and remove projectiles that leave it, the arena is the
+/// screen of the scenery. The contacts of removed projectiles are dropped so they don't hit
+/// anything later in the same tick
+pub struct ArenaSystem {}
+
+impl<'a> System<'a> for ArenaSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Critter>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Velocity>,
+        ReadStorage<'a, DamageCollider>,
+        ReadStorage<'a, Explosive>,
+        ReadExpect<'a, SceneryParams>,
+        WriteExpect<'a, Events>,
+        Write<'a, Contacts>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut positions,
+            critters,
+            colliders,
+            velocities,
+            damage_colliders,
+            explosives,
+            params,
+            mut events,
+            mut contacts,
+        ): Self::SystemData,
+    ) {
+        let size = params.screen_size;
+
+        for (pos, _, col) in (&mut positions, &critters, colliders.maybe()).join() {
+            // the whole body stays inside, unless the arena is smaller than it
+            let margin = V2::splat(col.map(|c| c.bounding_radius()).unwrap_or(0.0));
+            let min = margin.min(size * 0.5);
+            let max = (size - margin).max(size * 0.5);
+            pos.pos = pos.pos.clamp(min, max);
+        }
+
+        for (e, pos, _, _) in (&entities, &positions, &velocities, !&critters).join() {
+            if !damage_colliders.contains(e) && !explosives.contains(e) {
+                continue;
+            }
+
+            let inside = pos.pos.cmpge(V2::ZERO).all() && pos.pos.cmple(size).all();
+            if !inside {
+                log::debug!("deleting {:?}, it left the arena", e);
+                entities.delete(e).expect("fail to delete entity");
+                contacts.remove_entity(e);
+                events.removed.push(e);
+                events
+                    .game_events
+                    .push(GameEvent::ProjectileExpired { entity: e });
+            }
+        }
     }
 }
 
@@ -310,6 +1221,10 @@ impl<'a> System<'a> for DamageColliderSystem {
         WriteExpect<'a, Events>,
         WriteStorage<'a, Player>,
         ReadStorage<'a, Owner>,
+        ReadStorage<'a, Boss>,
+        WriteStorage<'a, StatusEffects>,
+        ReadExpect<'a, Frame>,
+        ReadStorage<'a, Obstacle>,
     );
 
     fn run(
@@ -323,17 +1238,36 @@ impl<'a> System<'a> for DamageColliderSystem {
             mut events,
             mut players,
             owners,
+            bosses,
+            mut statuses,
+            frame,
+            obstacles,
         ): Self::SystemData,
     ) {
         let mut hits = vec![];
+        // a disposable can touch many objects on the same tick, it is removed once
+        let mut disposed = vec![];
+        let mut blocked = vec![];
 
         for (a, b) in contacts.list().iter().copied() {
-            let a_team = teams.get(a);
             let a_damage = damage_colliders.get(a);
+            let b_damage = damage_colliders.get(b);
+            if a_damage.is_none() && b_damage.is_none() {
+                continue;
+            }
+
+            // obstacles stop disposable projectiles
+            for (projectile, damage, other) in [(a, a_damage, b), (b, b_damage, a)] {
+                if damage.map(|d| d.disposable).unwrap_or(false) && obstacles.contains(other) {
+                    log::trace!("{:?} blocked by {:?}, deleting it", projectile, other);
+                    blocked.push(projectile);
+                }
+            }
+
+            let a_team = teams.get(a);
             let a_damageable = damageables.get(a);
 
             let b_team = teams.get(b);
-            let b_damage = damage_colliders.get(b);
             let b_damageable = damageables.get(b);
 
             // check if a can damage b
@@ -343,11 +1277,11 @@ impl<'a> System<'a> for DamageColliderSystem {
                         source: a,
                         target: b,
                         amount: a_damage.damage,
+                        status: a_damage.status.clone(),
                     });
                     if a_damage.disposable {
                         log::trace!("{:?} hit {:?}, deleting it", a, b);
-                        entities.delete(a).unwrap();
-                        events.removed.push(a);
+                        disposed.push(a);
                     }
                 }
                 _ => {}
@@ -360,23 +1294,338 @@ impl<'a> System<'a> for DamageColliderSystem {
                         source: b,
                         target: a,
                         amount: b_damage.damage,
+                        status: b_damage.status.clone(),
                     });
                     if b_damage.disposable {
                         log::trace!("{:?} hit {:?}, deleting it", b, a);
-                        entities.delete(b).unwrap();
-                        events.removed.push(b);
+                        disposed.push(b);
                     }
                 }
                 _ => {}
             }
         }
 
+        disposed.sort();
+        disposed.dedup();
+        blocked.sort();
+        blocked.dedup();
+        blocked.retain(|e| disposed.binary_search(e).is_err());
+
+        for e in disposed.iter().chain(blocked.iter()) {
+            entities.delete(*e).unwrap();
+            events.removed.push(*e);
+        }
+        for e in blocked {
+            events
+                .game_events
+                .push(GameEvent::ProjectileExpired { entity: e });
+        }
+
+        for hit in hits {
+            damage::apply_status(&hit, frame.total_time, &owners, &mut statuses);
+            damage::process_hit(
+                hit,
+                &entities,
+                &mut events,
+                &owners,
+                &bosses,
+                &mut players,
+                &mut damageables,
+            );
+        }
+    }
+}
+
+pub struct ExplosiveSystem {}
+
+impl<'a> System<'a> for ExplosiveSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Explosive>,
+        ReadStorage<'a, Deadline>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Team>,
+        WriteStorage<'a, Damageable>,
+        ReadExpect<'a, Contacts>,
+        ReadExpect<'a, Frame>,
+        WriteExpect<'a, Events>,
+        WriteStorage<'a, Player>,
+        ReadStorage<'a, Owner>,
+        ReadStorage<'a, Boss>,
+        WriteStorage<'a, StatusEffects>,
+        ReadStorage<'a, Obstacle>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            explosives,
+            deadlines,
+            positions,
+            colliders,
+            teams,
+            mut damageables,
+            contacts,
+            frame,
+            mut events,
+            mut players,
+            owners,
+            bosses,
+            mut statuses,
+            obstacles,
+        ): Self::SystemData,
+    ) {
+        let mut detonations = vec![];
+
+        // detonate on contact with the affected team or an obstacle
+        for (a, b) in contacts.list().iter().copied() {
+            for (bomb, target) in [(a, b), (b, a)] {
+                let explosive = unwrap_or_continue!(explosives.get(bomb));
+                if (teams.get(target) == Some(&explosive.affects) && damageables.contains(target))
+                    || obstacles.contains(target)
+                {
+                    detonations.push(bomb);
+                }
+            }
+        }
+
+        // detonate by deadline
+        for (e, _, deadline) in (&entities, &explosives, &deadlines).join() {
+            if deadline.deadline.is_before(frame.total_time) {
+                detonations.push(e);
+            }
+        }
+
+        detonations.sort();
+        detonations.dedup();
+
+        let mut hits = vec![];
+
+        for bomb in detonations {
+            let explosive = unwrap_or_continue!(explosives.get(bomb));
+            let center = unwrap_or_continue!(positions.get(bomb)).pos;
+
+            for (target, pos, team, _, col) in (
+                &entities,
+                &positions,
+                &teams,
+                &damageables,
+                colliders.maybe(),
+            )
+                .join()
+            {
+                if *team != explosive.affects {
+                    continue;
+                }
+
+                if damage::is_in_radius(center, explosive.radius, pos.pos, col) {
+                    hits.push(damage::Hit {
+                        source: bomb,
+                        target,
+                        amount: explosive.damage,
+                        status: explosive.status.clone(),
+                    });
+                }
+            }
+
+            log::debug!("{:?} exploded at {:?}", bomb, center);
+            entities.delete(bomb).unwrap();
+            events.removed.push(bomb);
+            events.explosions.push(Explosion {
+                pos: center,
+                radius: explosive.radius,
+            });
+        }
+
+        for hit in hits {
+            damage::apply_status(&hit, frame.total_time, &owners, &mut statuses);
+            damage::process_hit(
+                hit,
+                &entities,
+                &mut events,
+                &owners,
+                &bosses,
+                &mut players,
+                &mut damageables,
+            );
+        }
+    }
+}
+
+pub struct AreaSystem {}
+
+impl<'a> System<'a> for AreaSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Area>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, Team>,
+        WriteStorage<'a, Damageable>,
+        ReadExpect<'a, Frame>,
+        WriteExpect<'a, Events>,
+        WriteStorage<'a, Player>,
+        ReadStorage<'a, Owner>,
+        ReadStorage<'a, Boss>,
+        WriteStorage<'a, StatusEffects>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            areas,
+            positions,
+            colliders,
+            teams,
+            mut damageables,
+            frame,
+            mut events,
+            mut players,
+            owners,
+            bosses,
+            mut statuses,
+        ): Self::SystemData,
+    ) {
+        let mut hits = vec![];
+
+        for (area_entity, area, area_pos) in (&entities, &areas, &positions).join() {
+            let amount = if area.instant {
+                area.damage
+            } else {
+                area.damage * frame.delta_time.as_seconds_f32()
+            };
+
+            for (target, pos, team, _, col) in (
+                &entities,
+                &positions,
+                &teams,
+                &damageables,
+                colliders.maybe(),
+            )
+                .join()
+            {
+                if *team != area.affects {
+                    continue;
+                }
+
+                if damage::is_in_radius(area_pos.pos, area.radius, pos.pos, col) {
+                    hits.push(damage::Hit {
+                        source: area_entity,
+                        target,
+                        amount,
+                        status: area.status.clone(),
+                    });
+                }
+            }
+
+            if area.instant {
+                log::trace!("{:?} instant area applied, deleting it", area_entity);
+                entities.delete(area_entity).unwrap();
+                events.explosions.push(Explosion {
+                    pos: area_pos.pos,
+                    radius: area.radius,
+                });
+            }
+        }
+
+        for hit in hits {
+            damage::apply_status(&hit, frame.total_time, &owners, &mut statuses);
+            damage::process_hit(
+                hit,
+                &entities,
+                &mut events,
+                &owners,
+                &bosses,
+                &mut players,
+                &mut damageables,
+            );
+        }
+    }
+}
+
+/// tick damage over time, expire status effects and apply their speed changes
+pub struct StatusSystem {}
+
+impl<'a> System<'a> for StatusSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, Critter>,
+        WriteStorage<'a, Caster>,
+        WriteStorage<'a, Damageable>,
+        WriteStorage<'a, Player>,
+        ReadStorage<'a, Owner>,
+        ReadStorage<'a, Boss>,
+        ReadExpect<'a, Frame>,
+        WriteExpect<'a, Events>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut statuses,
+            mut critters,
+            mut casters,
+            mut damageables,
+            mut players,
+            owners,
+            bosses,
+            frame,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        let now = frame.total_time;
+        let mut hits = vec![];
+
+        for (e, st, cri, cas) in (
+            &entities,
+            &mut statuses,
+            (&mut critters).maybe(),
+            (&mut casters).maybe(),
+        )
+            .join()
+        {
+            // without source the damage is credited to the target itself
+            for (source, amount) in st.take_damage_ticks(now) {
+                hits.push(damage::Hit {
+                    source: source.unwrap_or(e),
+                    target: e,
+                    amount,
+                    status: None,
+                });
+            }
+
+            st.expire(now);
+
+            // a stun interrupts the cast in progress, the mana spent is lost
+            if let Some(cas) = cas {
+                if st.is_stunned() && cas.casting.get_casting().is_some() {
+                    log::debug!("{:?} cast interrupted by stun", e);
+                    cas.casting = CasterState::Idle;
+                }
+            }
+
+            let cri = unwrap_or_continue!(cri);
+            let multiplier = st.speed_multiplier();
+            if multiplier < 1.0 {
+                let base_speed = *st.base_speed.get_or_insert(cri.speed);
+                cri.speed = base_speed * multiplier;
+            } else if let Some(base_speed) = st.base_speed.take() {
+                cri.speed = base_speed;
+            }
+        }
+
         for hit in hits {
             damage::process_hit(
                 hit,
                 &entities,
                 &mut events,
                 &owners,
+                &bosses,
                 &mut players,
                 &mut damageables,
             );
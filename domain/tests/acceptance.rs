@@ -4,26 +4,109 @@ use approx::assert_abs_diff_eq;
 use log::LevelFilter;
 use specs::prelude::*;
 
-use domain::components::*;
-use domain::models::*;
-use domain::player::{Player, PlayerInput};
-use domain::{cfg, unwrap_or_continue, Api};
+use domain::brain::{AimAndFireBrain, IdleBrain, KiteBrain, PlayerBrain, RandomWalkBrain};
+use domain::caster::CastError;
 use domain::caster::Caster;
 use domain::cfg::Cfg;
+use domain::components::*;
+use domain::events::{Explosion, Feedback, GameEvent};
+use domain::models::*;
+use domain::player::{Player, PlayerInput, PlayerUpgradeRequest, UpgradeError};
+use domain::replay::Replay;
+use domain::simulation::{run_simulation, SimulationParams};
+use domain::snapshot::Snapshot;
+use domain::spell::{AreaTarget, Spell, SpellAtLevel, SpellCode, SpellEffect};
+use domain::status::{StatusEffectCfg, StatusEffects, StatusKind};
+use domain::systems::WaveState;
+use domain::{cfg, loader, unwrap_or_continue, Api};
 
 const DELTA_TIME: DeltaTime = DeltaTime(0.1);
 
 fn new_scenery() -> Api {
+    new_scenery_with_cfg(Cfg::default())
+}
+
+fn new_scenery_with_cfg(cfg: Cfg) -> Api {
     let mut api = Api::default();
     api.start_scenery(SceneryParams {
         screen_size: screen_size(),
         seed: 0,
-        cfg: Cfg::default(),
+        cfg,
     })
     .unwrap();
     api
 }
 
+fn new_single_spell_cfg(effect: SpellEffect) -> Cfg {
+    Cfg {
+        spells: vec![Spell {
+            spell_code: SpellCode::from("spell"),
+            per_level: vec![SpellAtLevel {
+                mana_cost: 1.0,
+                cast_complexity: 0.1,
+                calm_down_complexity: 0.1,
+                effect,
+            }],
+        }],
+        ..Cfg::default()
+    }
+}
+
+fn new_explosive_cfg(ttl: DeltaTime) -> Cfg {
+    new_single_spell_cfg(SpellEffect::ExplosiveProject {
+        damage: 5.0,
+        speed: 100.0,
+        radius: 50.0,
+        ttl,
+        status: None,
+    })
+}
+
+/// add a critter without ai, so it will not move
+fn add_static_critter(api: &mut Api, pos: V2) -> Entity {
+    let enemy = api.get_scenery_params().cfg.enemies[0].clone();
+    let e = loader::new_critter(
+        api.world.create_entity(),
+        Position { pos, angle: 0.0 },
+        &enemy,
+    )
+    .build();
+    api.world.write_storage::<Ai>().remove(e);
+    e
+}
+
+fn get_hp(api: &Api, e: Entity) -> f32 {
+    api.world.read_storage::<Damageable>().get(e).unwrap().hp
+}
+
+fn cast_first_spell(api: &mut Api, mouse_pos: V2) {
+    let spell = api.get_scenery_params().cfg.spells[0].spell_code.clone();
+
+    let player_input = PlayerInput {
+        mouse_pos,
+        cast: Some(spell),
+        ..Default::default()
+    };
+    api.set_player_input(player_input).unwrap();
+}
+
+/// cast first spell of the cfg to the right and run until something explodes
+fn cast_and_wait_explosion(api: &mut Api, max_ticks: usize) -> Option<(TotalTime, Explosion)> {
+    let mouse_pos = get_mouse_angle_0(api);
+    cast_first_spell(api, mouse_pos);
+
+    for _ in 0..max_ticks {
+        api.update(DELTA_TIME).unwrap();
+        let mut events = api.take_events();
+        if let Some(explosion) = events.explosions.pop() {
+            let time = api.world.read_resource::<Frame>().total_time;
+            return Some((time, explosion));
+        }
+    }
+
+    None
+}
+
 fn screen_size() -> V2 {
     V2::new(600.0, 400.0)
 }
@@ -72,6 +155,8 @@ fn test_api_move_player_by_input_forward() {
         input_dir: V2::new(1.0, 0.0),
         mouse_pos: V2::ZERO,
         cast: None,
+        select_slot: None,
+        cast_selected: false,
         upgrade: None,
     })
     .unwrap();
@@ -84,6 +169,7 @@ fn test_api_move_player_by_input_forward() {
 }
 
 #[test]
+#[allow(clippy::useless_vec)]
 fn test_api_rotate_player() {
     let mut api = new_scenery();
 
@@ -99,6 +185,8 @@ fn test_api_rotate_player() {
             input_dir: V2::ZERO,
             mouse_pos,
             cast: None,
+            select_slot: None,
+            cast_selected: false,
             upgrade: None,
         })
         .unwrap();
@@ -111,6 +199,7 @@ fn test_api_rotate_player() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison, clippy::field_reassign_with_default)]
 fn test_api_cast() {
     try_init_log();
 
@@ -178,6 +267,1044 @@ fn test_api_cast() {
     assert_eq!(false, pd.casting.get_calm_down().is_some());
 }
 
+#[test]
+fn test_api_explosive_projectile_detonate_on_hit() {
+    let mut api = new_scenery_with_cfg(new_explosive_cfg(DeltaTime(5.0)));
+
+    // missile start at 350 and move 10 per tick to the right
+    let target = add_static_critter(&mut api, V2::new(400.0, 200.0));
+    let near_target = add_static_critter(&mut api, V2::new(420.0, 230.0));
+    let far_target = add_static_critter(&mut api, V2::new(500.0, 200.0));
+
+    let (time, explosion) = cast_and_wait_explosion(&mut api, 20).expect("no explosion");
+    assert!(time.as_seconds_f64() < 1.0);
+    assert!(explosion.pos.x < 400.0);
+    assert_abs_diff_eq!(200.0, explosion.pos.y);
+    assert_abs_diff_eq!(50.0, explosion.radius);
+
+    assert_abs_diff_eq!(5.0, get_hp(&api, target));
+    assert_abs_diff_eq!(5.0, get_hp(&api, near_target));
+    assert_abs_diff_eq!(10.0, get_hp(&api, far_target));
+
+    // player is never affected by own explosion
+    let (player, ..) = get_player_data(api.world.system_data());
+    assert_eq!(0, player.score());
+    let player_hp = {
+        let players = api.world.read_storage::<Player>();
+        let damageables = api.world.read_storage::<Damageable>();
+        (&players, &damageables).join().next().unwrap().1.hp
+    };
+    assert_abs_diff_eq!(100.0, player_hp);
+}
+
+#[test]
+fn test_api_explosive_projectile_detonate_on_timeout() {
+    let mut api = new_scenery_with_cfg(new_explosive_cfg(DeltaTime(0.5)));
+
+    // missile will explode near x 400 without touching the critters
+    let target_above = add_static_critter(&mut api, V2::new(400.0, 160.0));
+    let target_below = add_static_critter(&mut api, V2::new(410.0, 240.0));
+    let far_target = add_static_critter(&mut api, V2::new(400.0, 300.0));
+
+    let (_, explosion) = cast_and_wait_explosion(&mut api, 20).expect("no explosion");
+    assert!(explosion.pos.x > 390.0 && explosion.pos.x < 420.0);
+    assert_abs_diff_eq!(200.0, explosion.pos.y);
+
+    assert_abs_diff_eq!(5.0, get_hp(&api, target_above));
+    assert_abs_diff_eq!(5.0, get_hp(&api, target_below));
+    assert_abs_diff_eq!(10.0, get_hp(&api, far_target));
+
+    // missile is gone
+    let models = api.world.read_storage::<HasModel>();
+    let count = (&models,)
+        .join()
+        .filter(|(m,)| m.model.as_ref() == cfg::MODEL_FIREBALL)
+        .count();
+    assert_eq!(0, count);
+}
+
+#[test]
+fn test_api_area_instant_on_caster() {
+    let mut api = new_scenery_with_cfg(new_single_spell_cfg(SpellEffect::Area {
+        damage: 5.0,
+        radius: 50.0,
+        target: AreaTarget::Caster,
+        duration: DeltaTime(0.0),
+        status: None,
+    }));
+
+    let near_target = add_static_critter(&mut api, V2::new(340.0, 200.0));
+    let far_target = add_static_critter(&mut api, V2::new(300.0, 300.0));
+
+    let (_, explosion) = cast_and_wait_explosion(&mut api, 10).expect("no explosion");
+    assert_abs_diff_eq!(300.0, explosion.pos.x);
+    assert_abs_diff_eq!(200.0, explosion.pos.y);
+
+    // apply a few more ticks to check the damage is applied only once
+    for _ in 0..5 {
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    assert_abs_diff_eq!(5.0, get_hp(&api, near_target));
+    assert_abs_diff_eq!(10.0, get_hp(&api, far_target));
+
+    let areas = api.world.read_storage::<Area>();
+    assert_eq!(0, (&areas,).join().count());
+}
+
+#[test]
+fn test_api_area_persistent_on_aim() {
+    let mut api = new_scenery_with_cfg(new_single_spell_cfg(SpellEffect::Area {
+        damage: 4.0,
+        radius: 30.0,
+        target: AreaTarget::Aim,
+        duration: DeltaTime(1.0),
+        status: None,
+    }));
+
+    let target = add_static_critter(&mut api, V2::new(450.0, 250.0));
+    let far_target = add_static_critter(&mut api, V2::new(450.0, 320.0));
+
+    cast_first_spell(&mut api, V2::new(450.0, 250.0));
+
+    let mut area_entity = None;
+    let mut area_removed = false;
+    for _ in 0..30 {
+        api.update(DELTA_TIME).unwrap();
+        let events = api.take_events();
+
+        let models = api.world.read_storage::<HasModel>();
+        for e in events.added {
+            let has_model = unwrap_or_continue!(models.get(e));
+            if has_model.model.as_ref() == cfg::MODEL_AREA {
+                area_entity = Some(e);
+            }
+        }
+
+        if area_entity.is_some() && events.removed.contains(&area_entity.unwrap()) {
+            area_removed = true;
+        }
+    }
+
+    assert!(area_entity.is_some());
+    assert!(area_removed);
+
+    // 4 damage per second during 1 second
+    let hp = get_hp(&api, target);
+    assert!((6.0..6.5).contains(&hp), "unexpected hp {}", hp);
+    assert_abs_diff_eq!(10.0, get_hp(&api, far_target));
+}
+
+#[test]
+fn test_api_cast_selected_slot() {
+    let mut api = new_scenery();
+
+    let fireball = api.get_scenery_params().cfg.spells[1].clone();
+    assert_eq!(fireball.spell_code.as_ref(), "fireball");
+
+    // invalid slot is ignored
+    let player_input = PlayerInput {
+        mouse_pos: get_mouse_angle_0(&api),
+        select_slot: Some(99),
+        ..Default::default()
+    };
+    api.set_player_input(player_input).unwrap();
+    api.update(DELTA_TIME).unwrap();
+    let (player, ..) = get_player_data(api.world.system_data());
+    assert_eq!(0, player.selected_slot());
+
+    // select fireball and cast it
+    let player_input = PlayerInput {
+        mouse_pos: get_mouse_angle_0(&api),
+        select_slot: Some(1),
+        cast_selected: true,
+        ..Default::default()
+    };
+    api.set_player_input(player_input).unwrap();
+    api.update(DELTA_TIME).unwrap();
+
+    let (player, ..) = get_player_data(api.world.system_data());
+    assert_eq!(1, player.selected_slot());
+
+    let pd = get_player_casting(api.world.system_data());
+    assert!(pd.casting.get_casting().is_some());
+
+    let time_to_cast = fireball.per_level[0].time_to_cast(pd.casting_skill);
+    api.update(time_to_cast).unwrap();
+    check_added(&mut api, cfg::MODEL_FIREBALL, true);
+}
+
+#[test]
+fn test_api_feedback_on_failed_requests() {
+    let mut api = new_scenery();
+
+    {
+        let players = api.world.read_storage::<Player>();
+        let mut casters = api.world.write_storage::<Caster>();
+        let (_, caster) = (&players, &mut casters).join().next().unwrap();
+        caster.mana = 0.0;
+    }
+
+    let spell = api.get_scenery_params().cfg.spells[0].spell_code.clone();
+    let unknown = SpellCode::from("unknown");
+
+    let player_input = PlayerInput {
+        mouse_pos: get_mouse_angle_0(&api),
+        cast: Some(spell),
+        upgrade: Some(PlayerUpgradeRequest::Spell(unknown.clone())),
+        ..Default::default()
+    };
+    api.set_player_input(player_input).unwrap();
+    api.update(DELTA_TIME).unwrap();
+
+    let events = api.take_events();
+    assert_eq!(
+        vec![
+            Feedback::UpgradeFailed(UpgradeError::UnknownSpell(unknown)),
+            Feedback::CastFailed(CastError::NotEnoughMana),
+        ],
+        events.feedback
+    );
+
+    // feedback is only reported once
+    api.update(DELTA_TIME).unwrap();
+    assert!(api.take_events().feedback.is_empty());
+}
+
+fn get_player_entity(api: &Api) -> Entity {
+    let entities = api.world.entities();
+    let players = api.world.read_storage::<Player>();
+    (&entities, &players).join().next().unwrap().0
+}
+
+fn run_collecting_game_events(api: &mut Api, ticks: usize) -> Vec<GameEvent> {
+    let mut result = vec![];
+    for _ in 0..ticks {
+        api.update(DELTA_TIME).unwrap();
+        result.extend(api.take_events().game_events);
+    }
+    result
+}
+
+#[test]
+fn test_api_game_events_on_kill() {
+    let mut api = new_scenery();
+    let player = get_player_entity(&api);
+
+    let target = add_static_critter(&mut api, V2::new(400.0, 200.0));
+    api.world
+        .write_storage::<Damageable>()
+        .get_mut(target)
+        .unwrap()
+        .hp = 5.0;
+
+    let spell = api.get_scenery_params().cfg.spells[0].clone();
+    let mouse_pos = get_mouse_angle_0(&api);
+    cast_first_spell(&mut api, mouse_pos);
+    let events = run_collecting_game_events(&mut api, 15);
+
+    assert!(events.contains(&GameEvent::SpellCast {
+        caster: player,
+        spell: spell.spell_code.clone(),
+    }));
+    assert!(events.iter().any(|e| matches!(e,
+        GameEvent::Damaged { target: t, amount, .. } if *t == target && *amount == 10.0
+    )));
+    assert!(events.contains(&GameEvent::Killed {
+        entity: target,
+        killer: player,
+        score: 1,
+    }));
+    assert!(events.contains(&GameEvent::LevelUp { player, level: 1 }));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, GameEvent::ProjectileExpired { .. })));
+}
+
+#[test]
+fn test_api_game_events_projectile_expired_and_mana_depleted() {
+    let mut api = new_scenery_with_cfg(new_single_spell_cfg(SpellEffect::Projectile {
+        damage: 1.0,
+        speed: 10.0,
+        ttl: DeltaTime(0.3),
+        status: None,
+    }));
+    let player = get_player_entity(&api);
+
+    // only enough mana for a single cast
+    {
+        let players = api.world.read_storage::<Player>();
+        let mut casters = api.world.write_storage::<Caster>();
+        let (_, caster) = (&players, &mut casters).join().next().unwrap();
+        caster.mana = 1.5;
+        caster.mana_recharge = 0.0;
+    }
+
+    let mouse_pos = get_mouse_angle_0(&api);
+    cast_first_spell(&mut api, mouse_pos);
+    let events = run_collecting_game_events(&mut api, 10);
+
+    assert!(events.contains(&GameEvent::ManaDepleted { caster: player }));
+    assert!(events.contains(&GameEvent::CastStarted {
+        caster: player,
+        spell: SpellCode::from("spell"),
+        mana: 1.0,
+    }));
+    assert_eq!(
+        1,
+        events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::ProjectileExpired { .. }))
+            .count()
+    );
+}
+
+fn get_player_hp(api: &Api) -> f32 {
+    let player = get_player_entity(api);
+    get_hp(api, player)
+}
+
+#[test]
+fn test_api_player_death_and_restart() {
+    let mut api = new_scenery();
+    let player = get_player_entity(&api);
+    api.world
+        .write_storage::<Damageable>()
+        .get_mut(player)
+        .unwrap()
+        .hp = 0.5;
+
+    // critter touching the player
+    let critter = add_static_critter(&mut api, V2::new(320.0, 200.0));
+
+    let events = run_collecting_game_events(&mut api, 3);
+    assert!(events.contains(&GameEvent::PlayerDied { player }));
+
+    let outcome = match api.get_game_state() {
+        GameState::PlayerDead(outcome) => outcome,
+        other => panic!("unexpected state {:?}", other),
+    };
+    assert_eq!(0, outcome.score);
+    assert!(outcome.time_survived.as_seconds_f64() > 0.0);
+
+    // player is still available and simulation is stopped
+    assert!(get_player_hp(&api) < 0.0);
+    api.update(DELTA_TIME).unwrap();
+    assert_eq!(
+        outcome.time_survived,
+        api.world.read_resource::<Frame>().total_time
+    );
+
+    // restart
+    api.restart().unwrap();
+    assert_eq!(GameState::Running, api.get_game_state());
+    assert_eq!(
+        TotalTime::default(),
+        api.world.read_resource::<Frame>().total_time
+    );
+
+    let events = api.take_events();
+    assert!(events.removed.contains(&player));
+    assert!(events.removed.contains(&critter));
+    assert_eq!(1, api.world.entities().join().count());
+    assert_abs_diff_eq!(100.0, get_player_hp(&api));
+
+    api.update(DELTA_TIME).unwrap();
+    assert_eq!(GameState::Running, api.get_game_state());
+}
+
+#[test]
+fn test_api_pause_resume_and_step() {
+    let mut api = new_scenery();
+
+    let frame_before = (*api.world.read_resource::<Frame>()).clone();
+    let (player, pos_before, _) = get_player_data(api.world.system_data());
+    let skill_points = player.free_skill_points();
+
+    api.pause();
+    assert_eq!(GameState::Paused, api.get_game_state());
+
+    // move and upgrade while paused
+    api.set_player_input(PlayerInput {
+        input_dir: V2::new(1.0, 0.0),
+        upgrade: Some(PlayerUpgradeRequest::Health),
+        ..Default::default()
+    })
+    .unwrap();
+    api.update(DELTA_TIME).unwrap();
+
+    let frame = (*api.world.read_resource::<Frame>()).clone();
+    assert_eq!(frame_before.tick, frame.tick);
+    assert_eq!(frame_before.total_time, frame.total_time);
+
+    let (player, pos, _) = get_player_data(api.world.system_data());
+    assert_abs_diff_eq!(pos_before.pos.x, pos.pos.x);
+    assert_eq!(skill_points - 1, player.free_skill_points());
+
+    // single step while paused
+    api.step(2).unwrap();
+    assert_eq!(GameState::Paused, api.get_game_state());
+    let frame = (*api.world.read_resource::<Frame>()).clone();
+    assert_eq!(frame_before.tick + 2, frame.tick);
+    assert_abs_diff_eq!(
+        FIXED_DELTA_TIME.as_seconds_f32() as f64 * 2.0,
+        frame.total_time.as_seconds_f64(),
+        epsilon = 0.0001
+    );
+    let (_, pos, _) = get_player_data(api.world.system_data());
+    assert!(pos.pos.x > pos_before.pos.x);
+
+    // resume
+    api.resume();
+    assert_eq!(GameState::Running, api.get_game_state());
+    api.update(DELTA_TIME).unwrap();
+    assert_eq!(
+        frame_before.tick + 3,
+        api.world.read_resource::<Frame>().tick
+    );
+}
+
+#[test]
+fn test_api_player_death_while_stepping_paused() {
+    let mut api = new_scenery();
+    stop_spawns(&mut api);
+    let player = get_player_entity(&api);
+    api.world
+        .write_storage::<Damageable>()
+        .get_mut(player)
+        .unwrap()
+        .hp = -1.0;
+
+    api.pause();
+    api.step(3).unwrap();
+
+    // the death stops the stepping on the first tick
+    let frame = (*api.world.read_resource::<Frame>()).clone();
+    assert_eq!(1, frame.tick);
+    let outcome = match api.get_game_state() {
+        GameState::PlayerDead(outcome) => outcome,
+        other => panic!("unexpected state {:?}", other),
+    };
+    assert_eq!(frame.total_time, outcome.time_survived);
+
+    // resume does not bring the player back
+    api.resume();
+    assert!(matches!(api.get_game_state(), GameState::PlayerDead(_)));
+}
+
+#[test]
+fn test_fixed_timestep_interpolation() {
+    let mut api = new_scenery();
+    api.set_fixed_timestep(Some(DeltaTime(0.1)));
+
+    api.set_player_input(PlayerInput {
+        input_dir: V2::new(1.0, 0.0),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // not enough time for a tick
+    api.update(DeltaTime(0.05)).unwrap();
+    assert_eq!(0, api.world.read_resource::<Frame>().tick);
+    assert_abs_diff_eq!(0.5, api.get_interpolation_alpha(), epsilon = 0.001);
+
+    api.update(DeltaTime(0.1)).unwrap();
+    {
+        let frame = api.world.read_resource::<Frame>();
+        assert_eq!(1, frame.tick);
+        assert_abs_diff_eq!(0.1, frame.delta_time.as_seconds_f32());
+    }
+    assert_abs_diff_eq!(0.5, api.get_interpolation_alpha(), epsilon = 0.001);
+
+    // the previous position is the one before the tick
+    api.update(DeltaTime(0.1)).unwrap();
+    let (players, positions, prev_positions, critters): (
+        ReadStorage<Player>,
+        ReadStorage<Position>,
+        ReadStorage<PrevPosition>,
+        ReadStorage<Critter>,
+    ) = api.world.system_data();
+    let (_, pos, prev, critter) = (&players, &positions, &prev_positions, &critters)
+        .join()
+        .next()
+        .unwrap();
+    assert_abs_diff_eq!(critter.speed * 0.1, pos.pos.x - prev.pos.x, epsilon = 0.001);
+}
+
+#[test]
+fn test_replay_record_and_playback() {
+    let mut api = new_scenery();
+    api.start_recording().unwrap();
+
+    api.pause();
+    api.set_player_input(PlayerInput {
+        upgrade: Some(PlayerUpgradeRequest::Health),
+        ..Default::default()
+    })
+    .unwrap();
+    api.update(DELTA_TIME).unwrap();
+    api.resume();
+
+    for i in 0..200 {
+        let angle = i as f32 * 0.1;
+        api.set_player_input(PlayerInput {
+            input_dir: V2::new(angle.cos(), angle.sin()),
+            mouse_pos: screen_size() * 0.5 + V2::new(angle.sin(), angle.cos()) * 100.0,
+            cast_selected: i % 5 == 0,
+            ..Default::default()
+        })
+        .unwrap();
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    let replay = api.stop_recording().unwrap();
+    assert_eq!(201, replay.frames.len());
+    let replay = Replay::from_ron(&replay.to_ron().unwrap()).unwrap();
+
+    let mut playback = Api::default();
+    playback.play_replay(&replay).unwrap();
+
+    fn world_state(api: &Api) -> Vec<(Entity, V2, Option<Hp>)> {
+        let (entities, positions, damageables): (
+            Entities,
+            ReadStorage<Position>,
+            ReadStorage<Damageable>,
+        ) = api.world.system_data();
+        (&entities, &positions, damageables.maybe())
+            .join()
+            .map(|(e, pos, dam)| (e, pos.pos, dam.map(|d| d.hp)))
+            .collect()
+    }
+
+    let expected = world_state(&api);
+    assert!(expected.len() > 1);
+    assert_eq!(expected, world_state(&playback));
+    assert_eq!(
+        api.world.read_resource::<Frame>().tick,
+        playback.world.read_resource::<Frame>().tick
+    );
+
+    let (player, _, _) = get_player_data(api.world.system_data());
+    let (replayed_player, _, _) = get_player_data(playback.world.system_data());
+    assert_eq!(player.score(), replayed_player.score());
+    assert_eq!(
+        player.free_skill_points(),
+        replayed_player.free_skill_points()
+    );
+}
+
+#[test]
+fn test_replay_on_used_api() {
+    let mut api = new_scenery();
+    api.start_recording().unwrap();
+    run_scripted(&mut api, 0, 200);
+    let replay = api.stop_recording().unwrap();
+
+    let mut playback = new_scenery();
+    run_scripted(&mut playback, 0, 50);
+    playback.take_events();
+    let previous: Vec<Entity> = playback.world.entities().join().collect();
+
+    playback.play_replay(&replay).unwrap();
+
+    let expected = world_state(&api);
+    assert!(expected.len() > 1);
+    assert_eq!(expected, world_state(&playback));
+
+    let events = playback.take_events();
+    for e in &previous {
+        assert!(events.removed.contains(e));
+        assert!(!playback.world.is_alive(*e));
+    }
+}
+
+fn scripted_input(i: i32) -> PlayerInput {
+    let angle = i as f32 * 0.1;
+    PlayerInput {
+        input_dir: V2::new(angle.cos(), angle.sin()),
+        mouse_pos: screen_size() * 0.5 + V2::new(angle.sin(), angle.cos()) * 100.0,
+        cast_selected: i % 5 == 0,
+        ..Default::default()
+    }
+}
+
+fn run_scripted(api: &mut Api, from: i32, to: i32) {
+    for i in from..to {
+        api.set_player_input(scripted_input(i)).unwrap();
+        api.update(DELTA_TIME).unwrap();
+    }
+}
+
+fn world_state(api: &Api) -> Vec<(u32, u32, Option<u32>)> {
+    let (positions, damageables): (ReadStorage<Position>, ReadStorage<Damageable>) =
+        api.world.system_data();
+    let mut state: Vec<_> = (&positions, damageables.maybe())
+        .join()
+        .map(|(pos, dam)| {
+            (
+                pos.pos.x.to_bits(),
+                pos.pos.y.to_bits(),
+                dam.map(|d| d.hp.to_bits()),
+            )
+        })
+        .collect();
+    state.sort();
+    state
+}
+
+#[test]
+fn test_snapshot_save_and_load() {
+    let mut api = new_scenery();
+    run_scripted(&mut api, 0, 41);
+
+    let snapshot = api.save_snapshot().unwrap();
+    let snapshot = Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+
+    let mut loaded = Api::default();
+    loaded.load_snapshot(&snapshot).unwrap();
+    assert_eq!(world_state(&api), world_state(&loaded));
+
+    // projectiles keep the reference to the player
+    {
+        let (entities, players, owners): (Entities, ReadStorage<Player>, ReadStorage<Owner>) =
+            loaded.world.system_data();
+        let player = (&entities, &players).join().next().unwrap().0;
+        let owned: Vec<_> = (&owners).join().collect();
+        assert!(!owned.is_empty());
+        assert!(owned.iter().all(|owner| owner.entity == player));
+    }
+
+    let events = loaded.take_events();
+    assert_eq!(world_state(&api).len(), events.added.len());
+
+    run_scripted(&mut api, 41, 120);
+    run_scripted(&mut loaded, 41, 120);
+
+    assert_eq!(world_state(&api), world_state(&loaded));
+    assert_eq!(
+        api.world.read_resource::<Frame>().tick,
+        loaded.world.read_resource::<Frame>().tick
+    );
+    assert_eq!(api.get_game_state(), loaded.get_game_state());
+
+    // burn keeps crediting the player after a reload
+    let mut api = new_scenery();
+    stop_spawns(&mut api);
+    let target = add_static_critter(&mut api, V2::new(100.0, 100.0));
+    api.world
+        .write_storage::<Damageable>()
+        .get_mut(target)
+        .unwrap()
+        .hp = 1.0;
+    let burn = StatusEffectCfg {
+        kind: StatusKind::Burn,
+        duration: DeltaTime(2.0),
+        damage_per_second: 2.0,
+        slow: 0.0,
+    };
+    let player = get_player_entity(&api);
+    api.world
+        .write_storage::<StatusEffects>()
+        .get_mut(target)
+        .unwrap()
+        .apply(&burn, player, TotalTime::default());
+
+    let snapshot = api.save_snapshot().unwrap();
+    let snapshot = Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+    let mut loaded = Api::default();
+    loaded.load_snapshot(&snapshot).unwrap();
+    let player = get_player_entity(&loaded);
+
+    let events = run_collecting_game_events(&mut loaded, 20);
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, GameEvent::Killed { killer, .. } if *killer == player)));
+    let score = loaded
+        .world
+        .read_storage::<Player>()
+        .get(player)
+        .unwrap()
+        .score();
+    assert_eq!(1, score);
+}
+
+#[test]
+fn test_snapshot_save_does_not_change_the_game() {
+    let mut saved = new_scenery();
+    run_scripted(&mut saved, 0, 50);
+    saved.save_snapshot().unwrap();
+    run_scripted(&mut saved, 50, 400);
+
+    let mut api = new_scenery();
+    run_scripted(&mut api, 0, 400);
+
+    assert!(world_state(&api).len() > 1);
+    assert_eq!(world_state(&api), world_state(&saved));
+    assert_eq!(
+        api.save_snapshot().unwrap().to_json().unwrap(),
+        saved.save_snapshot().unwrap().to_json().unwrap()
+    );
+}
+
+#[test]
+fn test_simulation_stats() {
+    let params = SimulationParams {
+        duration: DeltaTime(20.0),
+        ..Default::default()
+    };
+    let stats = run_simulation(&params, &mut AimAndFireBrain).unwrap();
+    assert!(!stats.died);
+    assert_abs_diff_eq!(20.0, stats.time_survived.as_seconds_f64(), epsilon = 0.1);
+    assert!(stats.kills > 0);
+    // archetypes give at least one point per kill
+    assert!(stats.score >= stats.kills as i32);
+    assert!(stats.mana_spent > 0.0);
+
+    // an idle player is killed by the enemies
+    let mut cfg = Cfg::default();
+    cfg.player.hp = 1.0;
+    let params = SimulationParams {
+        cfg,
+        duration: DeltaTime(60.0),
+        ..Default::default()
+    };
+    let stats = run_simulation(&params, &mut IdleBrain).unwrap();
+    assert!(stats.died);
+    assert!(stats.time_survived.as_seconds_f64() < 60.0);
+    assert_eq!(0, stats.kills);
+    assert!(stats.damage_taken >= 1.0);
+}
+
+fn run_brain(api: &mut Api, brain: &mut dyn PlayerBrain, ticks: u32) {
+    for _ in 0..ticks {
+        let input = brain.next_input(&api.world);
+        api.set_player_input(input).unwrap();
+        api.update(DELTA_TIME).unwrap();
+    }
+}
+
+#[test]
+fn test_brain_kite_moves_away_from_enemy() {
+    let mut api = new_scenery();
+    let (_, pos, _) = get_player_data(api.world.system_data());
+    add_static_critter(&mut api, pos.pos + V2::new(100.0, 0.0));
+
+    run_brain(&mut api, &mut KiteBrain, 5);
+
+    let (_, new_pos, _) = get_player_data(api.world.system_data());
+    assert!(new_pos.pos.x < pos.pos.x);
+    let caster = get_player_casting(api.world.system_data());
+    assert!(caster.mana < caster.max_mana);
+}
+
+#[test]
+fn test_brain_aim_and_fire_kills_enemy() {
+    let mut api = new_scenery();
+    let (_, pos, _) = get_player_data(api.world.system_data());
+    let critter = add_static_critter(&mut api, pos.pos + V2::new(100.0, 0.0));
+
+    run_brain(&mut api, &mut AimAndFireBrain, 30);
+
+    assert!(!api.world.is_alive(critter));
+    let (player, new_pos, _) = get_player_data(api.world.system_data());
+    assert_eq!(pos.pos, new_pos.pos);
+    assert_eq!(1, player.score());
+}
+
+#[test]
+fn test_brain_random_walk_is_deterministic() {
+    let mut api1 = new_scenery();
+    let mut api2 = new_scenery();
+    run_brain(&mut api1, &mut RandomWalkBrain::new(5), 30);
+    run_brain(&mut api2, &mut RandomWalkBrain::new(5), 30);
+
+    let (_, pos1, _) = get_player_data(api1.world.system_data());
+    let (_, pos2, _) = get_player_data(api2.world.system_data());
+    let start = screen_size() * 0.5;
+    assert_ne!(start, pos1.pos);
+    assert_eq!(pos1.pos, pos2.pos);
+}
+
+fn count_projectiles_expired(events: &[GameEvent]) -> usize {
+    events
+        .iter()
+        .filter(|e| matches!(e, GameEvent::ProjectileExpired { .. }))
+        .count()
+}
+
+#[test]
+fn test_arena_bounds() {
+    // a single enemy in the first wave, so the projectile is not stopped by others
+    let mut cfg = Cfg::default();
+    cfg.spawn.waves[0].enemies[0].count = 1;
+    let mut api = new_scenery_with_cfg(cfg);
+    let player = get_player_entity(&api);
+
+    api.set_player_input(PlayerInput {
+        input_dir: V2::new(-1.0, -1.0),
+        ..Default::default()
+    })
+    .unwrap();
+    for _ in 0..50 {
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    // the collider radius keeps the whole body inside
+    let (_, pos, _) = get_player_data(api.world.system_data());
+    assert_abs_diff_eq!(12.0, pos.pos.x);
+    assert_abs_diff_eq!(12.0, pos.pos.y);
+
+    // long ttl, the projectile is removed by leaving the arena
+    let mouse_pos = pos.pos + V2::new(1.0, 0.0);
+    cast_first_spell(&mut api, mouse_pos);
+    let events = run_collecting_game_events(&mut api, 30);
+    assert_eq!(1, count_projectiles_expired(&events));
+    let critters = api.world.read_storage::<Critter>();
+    let damage_colliders = api.world.read_storage::<DamageCollider>();
+    assert_eq!(0, (&damage_colliders, !&critters).join().count());
+    assert!(api.world.is_alive(player));
+}
+
+#[test]
+fn test_obstacles_block_player_and_projectiles() {
+    let mut cfg = Cfg::default();
+    cfg.obstacles.push(cfg::ObstacleCfg {
+        model: "pillar".into(),
+        pos: screen_size() * 0.5 + V2::new(100.0, 0.0),
+        angle: 0.0,
+        shape: Shape::Aabb {
+            half_extents: V2::new(20.0, 50.0),
+        },
+        scale: 1.0,
+    });
+    let mut api = new_scenery_with_cfg(cfg);
+    check_added(&mut api, "pillar", true);
+
+    // projectile stops on the obstacle without damaging it
+    let mouse_pos = get_mouse_angle_0(&api);
+    cast_first_spell(&mut api, mouse_pos);
+    let events = run_collecting_game_events(&mut api, 20);
+    assert_eq!(1, count_projectiles_expired(&events));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, GameEvent::Damaged { .. })));
+
+    api.set_player_input(PlayerInput {
+        input_dir: V2::new(1.0, 0.0),
+        ..Default::default()
+    })
+    .unwrap();
+    for _ in 0..20 {
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    // the player is pushed out of the obstacle, that never moves
+    let (_, pos, _) = get_player_data(api.world.system_data());
+    assert_abs_diff_eq!(screen_size().x * 0.5 + 100.0 - 20.0 - 12.0, pos.pos.x);
+    let obstacles = api.world.read_storage::<Obstacle>();
+    let positions = api.world.read_storage::<Position>();
+    let (_, obstacle_pos) = (&obstacles, &positions).join().next().unwrap();
+    assert_eq!(screen_size() * 0.5 + V2::new(100.0, 0.0), obstacle_pos.pos);
+}
+
+fn distance_to_player(api: &Api, e: Entity) -> f32 {
+    let (_, player_pos, _) = get_player_data(api.world.system_data());
+    let positions = api.world.read_storage::<Position>();
+    positions.get(e).unwrap().pos.distance(player_pos.pos)
+}
+
+#[test]
+fn test_ai_flow_field_walks_around_obstacles() {
+    let mut cfg = Cfg::default();
+    // wall between the player and the critters
+    cfg.obstacles.push(cfg::ObstacleCfg {
+        model: "wall".into(),
+        pos: screen_size() * 0.5 - V2::new(100.0, 0.0),
+        angle: 0.0,
+        shape: Shape::Aabb {
+            half_extents: V2::new(10.0, 80.0),
+        },
+        scale: 1.0,
+    });
+    let mut api = new_scenery_with_cfg(cfg);
+    // only the tested critters
+    stop_spawns(&mut api);
+
+    let enemy = api.get_scenery_params().cfg.enemies[0].clone();
+    let mut new_critter = |ai: Ai, y: f32| {
+        let pos = V2::new(screen_size().x * 0.5 - 200.0, screen_size().y * 0.5 + y);
+        let e = loader::new_critter(
+            api.world.create_entity(),
+            Position { pos, angle: 0.0 },
+            &enemy,
+        )
+        .build();
+        api.world.write_storage::<Ai>().insert(e, ai).unwrap();
+        e
+    };
+    // apart so they do not push each other at the start
+    let straight = new_critter(Ai::FollowPlayer, -20.0);
+    let flow = new_critter(Ai::FollowFlowField, 20.0);
+
+    for _ in 0..100 {
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    assert!(distance_to_player(&api, straight) > 100.0);
+    assert!(distance_to_player(&api, flow) < 40.0);
+}
+
+/// keep the spawner on a break before the first wave
+fn stop_spawns(api: &mut Api) {
+    api.enemy_system.state = WaveState::Break {
+        until: TotalTime::from(1000.0),
+    };
+}
+
+/// scenery without random spawns, with a critter of the archetype
+fn new_scenery_with_archetype(code: &str, pos: V2) -> (Api, Entity) {
+    let mut api = new_scenery();
+    stop_spawns(&mut api);
+    let enemy = api
+        .get_scenery_params()
+        .cfg
+        .find_enemy(code)
+        .unwrap()
+        .clone();
+    let e = loader::new_critter(
+        api.world.create_entity(),
+        Position { pos, angle: 0.0 },
+        &enemy,
+    )
+    .build();
+    (api, e)
+}
+
+#[test]
+fn test_ai_ranged_keeps_distance_and_fires() {
+    let start = screen_size() * 0.5 + V2::new(40.0, 0.0);
+    let (mut api, ranged) = new_scenery_with_archetype("ranged", start);
+    let player = get_player_entity(&api);
+
+    let events = run_collecting_game_events(&mut api, 100);
+
+    // walks away to the preferred distance, 250 with 10% tolerance
+    let distance = distance_to_player(&api, ranged);
+    assert!((225.0..=275.0).contains(&distance), "{}", distance);
+    assert!(events.iter().any(|e| matches!(e,
+        GameEvent::Damaged { target, .. } if *target == player
+    )));
+}
+
+#[test]
+fn test_enemy_caster_projectiles_affect_player() {
+    let start = screen_size() * 0.5 + V2::new(0.0, 150.0);
+    let (mut api, ranged) = new_scenery_with_archetype("ranged", start);
+
+    let mut missile = None;
+    for _ in 0..20 {
+        api.update(DELTA_TIME).unwrap();
+        let events = api.take_events();
+        if events.game_events.contains(&GameEvent::SpellCast {
+            caster: ranged,
+            spell: SpellCode::from("enemy_bolt"),
+        }) {
+            missile = events.added.first().copied();
+            break;
+        }
+    }
+
+    let missile = missile.expect("ranged enemy never cast");
+    let damage_colliders = api.world.read_storage::<DamageCollider>();
+    assert_eq!(Team::Player, damage_colliders.get(missile).unwrap().affects);
+    let owners = api.world.read_storage::<Owner>();
+    assert_eq!(ranged, owners.get(missile).unwrap().entity);
+
+    // aimed at the player, above the caster
+    let velocities = api.world.read_storage::<Velocity>();
+    let vel = velocities.get(missile).unwrap().vel;
+    assert_abs_diff_eq!(0.0, vel.x, epsilon = 0.001);
+    assert!(vel.y < 0.0);
+}
+
+#[test]
+fn test_ai_charger_winds_up_and_dashes() {
+    let start = screen_size() * 0.5 - V2::new(150.0, 0.0);
+    let (mut api, charger) = new_scenery_with_archetype("charger", start);
+
+    let get_state = |api: &Api| match api.world.read_storage::<Ai>().get(charger) {
+        Some(Ai::Charger { state, .. }) => state.clone(),
+        other => panic!("unexpected ai {:?}", other),
+    };
+    let get_speed = |api: &Api| {
+        let velocities = api.world.read_storage::<Velocity>();
+        velocities.get(charger).unwrap().vel.length()
+    };
+
+    // in range from the start
+    api.update(DELTA_TIME).unwrap();
+    assert!(matches!(get_state(&api), ChargeState::WindUp { .. }));
+    assert_eq!(0.0, get_speed(&api));
+
+    let mut dashed = false;
+    for _ in 0..10 {
+        api.update(DELTA_TIME).unwrap();
+        if let ChargeState::Dash { dir, .. } = get_state(&api) {
+            assert_abs_diff_eq!(1.0, dir.x);
+            assert_abs_diff_eq!(300.0, get_speed(&api));
+            dashed = true;
+            break;
+        }
+    }
+    assert!(dashed);
+
+    for _ in 0..10 {
+        api.update(DELTA_TIME).unwrap();
+    }
+    assert!(matches!(get_state(&api), ChargeState::Recover { .. }));
+}
+
+#[test]
+fn test_ai_swarmers_flock() {
+    let start = V2::new(50.0, 50.0);
+    let (mut api, first) = new_scenery_with_archetype("swarmer", start);
+    let enemy = api
+        .get_scenery_params()
+        .cfg
+        .find_enemy("swarmer")
+        .unwrap()
+        .clone();
+    let second = loader::new_critter(
+        api.world.create_entity(),
+        Position {
+            pos: start + V2::new(0.0, 1.0),
+            angle: 0.0,
+        },
+        &enemy,
+    )
+    .build();
+
+    let distance_between = |api: &Api| {
+        let positions = api.world.read_storage::<Position>();
+        positions
+            .get(first)
+            .unwrap()
+            .pos
+            .distance(positions.get(second).unwrap().pos)
+    };
+    let start_distance = distance_to_player(&api, first);
+
+    for _ in 0..10 {
+        api.update(DELTA_TIME).unwrap();
+    }
+
+    // they chase together, but do not stack
+    assert!(distance_to_player(&api, first) < start_distance - 50.0);
+    assert!(distance_between(&api) > 16.0);
+    assert!(distance_between(&api) < 60.0);
+}
+
+#[allow(clippy::bool_assert_comparison)]
 fn check_added(api: &mut Api, model: &str, expected: bool) {
     let events = api.take_events();
     let storage = api.world.read_storage::<HasModel>();
@@ -196,3 +1323,260 @@ fn try_init_log() {
         .filter_level(LevelFilter::Trace)
         .try_init();
 }
+
+fn count_enemies(api: &Api) -> usize {
+    let critters = api.world.read_storage::<Critter>();
+    let teams = api.world.read_storage::<Team>();
+    (&critters, &teams)
+        .join()
+        .filter(|(_, team)| **team == Team::Enemy)
+        .count()
+}
+
+#[test]
+fn test_waves() {
+    let mut cfg = Cfg::default();
+    cfg.spawn.waves = vec![
+        cfg::WaveCfg {
+            enemies: vec![cfg::WaveEntry {
+                enemy: cfg.enemies[0].code.clone(),
+                count: 2,
+            }],
+            interval: DeltaTime(0.5),
+            boss: None,
+        },
+        cfg::WaveCfg {
+            enemies: vec![],
+            interval: DeltaTime(0.5),
+            boss: Some(cfg.enemies[0].code.clone()),
+        },
+    ];
+    cfg.spawn.break_time = DeltaTime(1.0);
+    let mut api = new_scenery_with_cfg(cfg);
+
+    api.update(DELTA_TIME).unwrap();
+    let events = api.take_events();
+    assert!(events.game_events.contains(&GameEvent::WaveStarted {
+        wave: 1,
+        boss: false
+    }));
+    assert_eq!(1, count_enemies(&api));
+
+    for _ in 0..10 {
+        api.update(DELTA_TIME).unwrap();
+    }
+    assert_eq!(2, count_enemies(&api));
+    assert!(!api
+        .take_events()
+        .game_events
+        .contains(&GameEvent::WaveCleared { wave: 1 }));
+
+    // kill the wave
+    let enemies: Vec<Entity> = {
+        let entities = api.world.entities();
+        let critters = api.world.read_storage::<Critter>();
+        let teams = api.world.read_storage::<Team>();
+        (&entities, &critters, &teams)
+            .join()
+            .filter(|(_, _, team)| **team == Team::Enemy)
+            .map(|(e, _, _)| e)
+            .collect()
+    };
+    api.world.delete_entities(&enemies).unwrap();
+
+    api.update(DELTA_TIME).unwrap();
+    let events = api.take_events();
+    assert!(events
+        .game_events
+        .contains(&GameEvent::WaveCleared { wave: 1 }));
+
+    // boss wave after the break, the boss is spawned at the start
+    let mut started = false;
+    for _ in 0..15 {
+        api.update(DELTA_TIME).unwrap();
+        started |= api
+            .take_events()
+            .game_events
+            .contains(&GameEvent::WaveStarted {
+                wave: 2,
+                boss: true,
+            });
+    }
+    assert!(started);
+    assert_eq!(1, count_enemies(&api));
+}
+
+fn set_hp_fraction(api: &mut Api, e: Entity, fraction: f32) {
+    let mut damageables = api.world.write_storage::<Damageable>();
+    let dam = damageables.get_mut(e).unwrap();
+    dam.hp = dam.max_hp * fraction;
+}
+
+#[test]
+fn test_boss_phases_and_defeat() {
+    let start = screen_size() * 0.5 + V2::new(150.0, 0.0);
+    let (mut api, boss) = new_scenery_with_archetype("boss_1", start);
+    let player = get_player_entity(&api);
+
+    let events = run_collecting_game_events(&mut api, 1);
+    assert!(events.contains(&GameEvent::BossPhaseChanged { boss, phase: 0 }));
+
+    // radial bursts
+    set_hp_fraction(&mut api, boss, 0.5);
+    let events = run_collecting_game_events(&mut api, 1);
+    assert!(events.contains(&GameEvent::BossPhaseChanged { boss, phase: 1 }));
+    assert!(matches!(
+        api.world.read_storage::<Ai>().get(boss),
+        Some(Ai::Ranged(_))
+    ));
+
+    let mut burst = 0;
+    for _ in 0..40 {
+        api.update(DELTA_TIME).unwrap();
+        let owners = api.world.read_storage::<Owner>();
+        let colliders = api.world.read_storage::<DamageCollider>();
+        burst = (&owners, &colliders)
+            .join()
+            .filter(|(owner, _)| owner.entity == boss)
+            .count();
+        if burst > 0 {
+            break;
+        }
+    }
+    assert_eq!(12, burst);
+
+    // enrage
+    set_hp_fraction(&mut api, boss, 0.2);
+    let events = run_collecting_game_events(&mut api, 1);
+    assert!(events.contains(&GameEvent::BossPhaseChanged { boss, phase: 2 }));
+    assert_abs_diff_eq!(
+        75.0,
+        api.world.read_storage::<Critter>().get(boss).unwrap().speed
+    );
+
+    api.world
+        .write_storage::<Damageable>()
+        .get_mut(boss)
+        .unwrap()
+        .hp = 5.0;
+    let boss_pos = api.world.read_storage::<Position>().get(boss).unwrap().pos;
+    cast_first_spell(&mut api, boss_pos);
+    let events = run_collecting_game_events(&mut api, 15);
+
+    assert!(events.contains(&GameEvent::BossDefeated {
+        boss,
+        killer: player,
+        score: 100,
+    }));
+    let score = api
+        .world
+        .read_storage::<Player>()
+        .get(player)
+        .unwrap()
+        .score();
+    assert_eq!(120, score);
+}
+
+fn apply_status(api: &mut Api, target: Entity, kind: StatusKind, duration: f32) {
+    let source = get_player_entity(api);
+    let now = api.world.read_resource::<Frame>().total_time;
+    let cfg = StatusEffectCfg {
+        kind,
+        duration: DeltaTime(duration),
+        damage_per_second: 0.0,
+        slow: 0.5,
+    };
+    api.world
+        .write_storage::<StatusEffects>()
+        .get_mut(target)
+        .unwrap()
+        .apply(&cfg, source, now);
+}
+
+#[test]
+fn test_status_effects() {
+    let mut api = new_scenery_with_cfg(new_single_spell_cfg(SpellEffect::Projectile {
+        damage: 1.0,
+        speed: 200.0,
+        ttl: DeltaTime(2.0),
+        status: Some(StatusEffectCfg {
+            kind: StatusKind::Burn,
+            duration: DeltaTime(2.0),
+            damage_per_second: 2.0,
+            slow: 0.0,
+        }),
+    }));
+    stop_spawns(&mut api);
+    let player = get_player_entity(&api);
+
+    // burn keeps damaging the target after the projectile hit
+    let pos = get_mouse_angle_0(&api) + V2::new(100.0, 0.0);
+    let target = add_static_critter(&mut api, pos);
+    let max_hp = get_hp(&api, target);
+    cast_first_spell(&mut api, pos);
+    let events = run_collecting_game_events(&mut api, 40);
+    let hits = events
+        .iter()
+        .filter(|e| matches!(e, GameEvent::Damaged { target: t, .. } if *t == target))
+        .count();
+    assert_eq!(5, hits);
+    assert_abs_diff_eq!(max_hp - 5.0, get_hp(&api, target), epsilon = 0.001);
+
+    // slow changes the speed until it expires
+    let speed = api
+        .world
+        .read_storage::<Critter>()
+        .get(target)
+        .unwrap()
+        .speed;
+    apply_status(&mut api, target, StatusKind::Slow, 1.0);
+    api.update(DELTA_TIME).unwrap();
+    assert_abs_diff_eq!(
+        speed * 0.5,
+        api.world
+            .read_storage::<Critter>()
+            .get(target)
+            .unwrap()
+            .speed
+    );
+    run_collecting_game_events(&mut api, 10);
+    assert_abs_diff_eq!(
+        speed,
+        api.world
+            .read_storage::<Critter>()
+            .get(target)
+            .unwrap()
+            .speed
+    );
+
+    // a stunned player can not cast
+    apply_status(&mut api, player, StatusKind::Stun, 1.0);
+    cast_first_spell(&mut api, pos);
+    api.update(DELTA_TIME).unwrap();
+    assert_eq!(
+        vec![Feedback::CastFailed(CastError::Stunned)],
+        api.take_events().feedback
+    );
+}
+
+#[test]
+fn test_stun_interrupts_cast() {
+    let mut api = new_scenery();
+    stop_spawns(&mut api);
+    let player = get_player_entity(&api);
+
+    let mouse_pos = get_mouse_angle_0(&api);
+    cast_first_spell(&mut api, mouse_pos);
+    api.update(DELTA_TIME).unwrap();
+    let pd = get_player_casting(api.world.system_data());
+    assert!(pd.casting.get_casting().is_some());
+
+    apply_status(&mut api, player, StatusKind::Stun, 0.5);
+    let events = run_collecting_game_events(&mut api, 10);
+
+    let pd = get_player_casting(api.world.system_data());
+    assert!(pd.casting.is_idle());
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, GameEvent::SpellCast { caster, .. } if *caster == player)));
+}
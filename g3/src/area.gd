@@ -0,0 +1,18 @@
+extends Node2D
+
+var dto
+
+func set_dto(obj):
+	dto = obj
+	position = dto.pos
+	update()
+
+func update_dto(obj):
+	position = obj.pos
+
+func _draw():
+	if dto == null:
+		return
+
+	draw_circle(Vector2.ZERO, dto.radius, Color(1, 0.4, 0.1, 0.35))
+	draw_arc(Vector2.ZERO, dto.radius, 0, TAU, 48, Color(1, 0.5, 0.1, 0.8), 2)
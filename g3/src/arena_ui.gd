@@ -4,6 +4,9 @@ signal on_upgrade_button_pressed(code)
 
 onready var label = $DescriptionLabel
 onready var upgrade_buttons = $UpgradeContainer
+onready var boss_bar = $BossBar
+onready var paused_label = $PausedLabel
+onready var game_over_label = $GameOverLabel
 
 func update_dto(player_dto):
 	var fmt = "HP: {0}/{1}\nMana: {2}/{3}\nCasting: {4}\nCalm down: {5}\nScore: {6}/{7}\nLevel: {8}\nSkill: {9}"
@@ -24,5 +27,25 @@ func update_dto(player_dto):
 	upgrade_buttons.visible = player_dto.free_skill_points > 0;
 
 
+# hp bar of the first boss alive, hidden without bosses
+func update_bosses(bosses):
+	boss_bar.visible = bosses.size() > 0
+	if bosses.size() > 0:
+		boss_bar.max_value = bosses[0].max_hp
+		boss_bar.value = bosses[0].hp
+
+
+# upgrades are still applied while paused, the label invites to spend the skill points
+func update_game_state(game_state):
+	paused_label.visible = game_state.state == "paused"
+	game_over_label.visible = game_state.state == "player_dead"
+	if game_over_label.visible:
+		game_over_label.text = "Game over\nScore: {0}\nLevel: {1}\nSurvived: {2}s\nPress R to restart".format([
+			game_state.score,
+			game_state.level,
+			stepify(game_state.time_survived, 0.1)
+		])
+
+
 func _on_upgrade_button_pressed(code):
 	emit_signal("on_upgrade_button_pressed", code)
@@ -9,6 +9,19 @@ var request_upgrade = ""
 
 var on_click = false
 
+# running, paused or player_dead, from the last output
+var game_state = "running"
+
+# enemy archetypes share the same scene with a different tint
+var enemy_models = {
+	"enemy_1": Color(1, 1, 1),
+	"enemy_ranged": Color(0.6, 0.8, 1),
+	"enemy_charger": Color(1, 0.6, 0.4),
+	"enemy_swarmer": Color(0.7, 1, 0.6),
+	"enemy_tank": Color(0.6, 0.6, 0.6),
+	"boss_1": Color(1, 0.3, 0.3),
+}
+
 func _ready():
 	ui.connect("on_upgrade_button_pressed", self, "_on_click_skill_upgrade")
 	
@@ -28,6 +41,10 @@ func _process(delta):
 	if Input.is_action_pressed("ui_up"):
 		gi.input.y -= 1
 
+	for i in range(4):
+		if Input.is_key_pressed(KEY_1 + i):
+			gi.select_slot = i
+
 	if on_click:
 		gi.mouse_press = true
 
@@ -47,28 +64,54 @@ func _process(delta):
 
 	# update player ui
 	ui.update_dto(output.player)
+	ui.update_bosses(output.bosses)
+	ui.update_game_state(output.game_state)
+	game_state = output.game_state.state
 
 	# process events
 	for id in output.removed:
 		print("removing ", id)
-		idmap[id].queue_free()
-		idmap.erase(id)
+		if idmap.has(id):
+			idmap[id].queue_free()
+			idmap.erase(id)
 		
 	for obj in output.added:
 		print("added ", obj)
-		if obj.model == "magic_missile":
+		if obj.model == "magic_missile" or obj.model == "fireball":
 			var node = load("res://scenes/missile.tscn").instance()
 			get_node("../objects").add_child(node)
 			node.update_dto(obj)
 			idmap[obj.id] = node
-		elif obj.model == "enemy_1":
+		elif enemy_models.has(obj.model):
 			var node = load("res://scenes/enemy_1.tscn").instance()
+			node.modulate = enemy_models[obj.model]
+			if obj.model.begins_with("boss"):
+				node.scale = Vector2(2, 2)
 			get_node("../objects").add_child(node)
 			node.update_dto(obj)
 			idmap[obj.id] = node
+		elif obj.model == "area":
+			var node = Node2D.new()
+			node.set_script(load("res://src/area.gd"))
+			get_node("../objects").add_child(node)
+			node.set_dto(obj)
+			idmap[obj.id] = node
 		else:
 			print("invalid model ", obj)
 
+	for obstacle in output.added_obstacles:
+		var node = Node2D.new()
+		node.set_script(load("res://src/obstacle.gd"))
+		get_node("../objects").add_child(node)
+		node.set_dto(obstacle)
+		idmap[obstacle.id] = node
+
+	for explosion in output.explosions:
+		var node = Node2D.new()
+		node.set_script(load("res://src/explosion.gd"))
+		get_node("../objects").add_child(node)
+		node.set_dto(explosion)
+
 	for obj in output.objects:
 		if idmap.has(obj.id):
 			idmap[obj.id].update_dto(obj)
@@ -76,10 +119,24 @@ func _process(delta):
 			print("invalid obj ", obj)
 	
 
+# the api reports the old entities as removed, the nodes are dropped now so none is left behind
+func restart():
+	for id in idmap:
+		idmap[id].queue_free()
+	idmap.clear()
+	api.restart()
+
 func _on_click_skill_upgrade(code):
 	request_upgrade = code
 
 func _unhandled_input(event):
+	if event.is_action_pressed("ui_cancel"):
+		if game_state == "paused":
+			api.resume()
+		elif game_state == "running":
+			api.pause()
+	if game_state == "player_dead" and event is InputEventKey and event.pressed and event.scancode == KEY_R:
+		restart()
 	if event is InputEventMouseButton:
 		if event.button_index == 1:
 			on_click = event.pressed
@@ -2,6 +2,14 @@ extends Node2D
 
 var animation
 
+const status_colors = {
+	"stun": Color(1, 1, 0.4),
+	"freeze": Color(0.5, 0.8, 1),
+	"burn": Color(1, 0.5, 0.3),
+	"poison": Color(0.5, 1, 0.4),
+	"slow": Color(0.7, 0.7, 1),
+}
+
 func _ready():
 	animation = get_node("AnimatedSprite")
 
@@ -13,3 +21,12 @@ func update_dto(dto):
 		animation.animation = "walk"
 	else:
 		animation.animation = "idle"
+
+	animation.modulate = status_color(dto.statuses)
+
+# tint of the sprite for the strongest active status
+func status_color(statuses):
+	for status in ["stun", "freeze", "burn", "poison", "slow"]:
+		if statuses.has(status):
+			return status_colors[status]
+	return Color(1, 1, 1)
@@ -0,0 +1,28 @@
+extends Node2D
+
+# seconds the explosion stays visible
+const DURATION = 0.3
+
+var dto
+var elapsed = 0.0
+
+func set_dto(explosion):
+	dto = explosion
+	position = dto.pos
+	update()
+
+func _process(delta):
+	elapsed += delta
+	if elapsed >= DURATION:
+		queue_free()
+	else:
+		update()
+
+func _draw():
+	if dto == null:
+		return
+
+	# grows to the full radius while fading out
+	var t = elapsed / DURATION
+	var color = Color(1, 0.55, 0.2, 1.0 - t)
+	draw_circle(Vector2.ZERO, dto.radius * (0.5 + 0.5 * t), color)
@@ -0,0 +1,30 @@
+extends Node2D
+
+var dto
+
+func set_dto(obstacle):
+	dto = obstacle
+	position = dto.pos
+	# aabb ignores the angle
+	if dto.shape != "aabb":
+		rotation = dto.angle
+	update()
+
+func update_dto(_obj):
+	# obstacles never move
+	pass
+
+func _draw():
+	if dto == null:
+		return
+
+	var color = Color(0.45, 0.4, 0.35)
+	if dto.shape == "circle":
+		draw_circle(Vector2.ZERO, dto.radius, color)
+	elif dto.shape == "capsule":
+		var half = Vector2(dto.half_extents.x, 0)
+		draw_line(-half, half, color, dto.radius * 2)
+		draw_circle(-half, dto.radius, color)
+		draw_circle(half, dto.radius, color)
+	else:
+		draw_rect(Rect2(-dto.half_extents, dto.half_extents * 2), color)
@@ -1,12 +1,19 @@
+use std::path::Path;
+
 use gdnative::prelude::*;
 use specs::prelude::*;
-use domain::caster::Caster;
+use domain::caster::{CastError, Caster};
 use domain::cfg::Cfg;
 
 use domain::components::*;
 use domain::error::GameError;
-use domain::models::{DeltaTime, SceneryParams};
+use domain::events::{Feedback, GameEvent};
+use domain::models::{DeltaTime, GameState, SceneryParams};
 use domain::player::*;
+use domain::replay::Replay;
+use domain::snapshot::Snapshot;
+use domain::spell::{SpellCode, SpellSlot};
+use domain::status::{StatusEffects, StatusKind};
 
 use crate::utils::*;
 
@@ -18,12 +25,17 @@ pub type Id = u64;
 #[inherit(Node)]
 pub struct GameApi {
     api: domain::Api,
+    /// mouse state of the previous update, casts only trigger on the press edge
+    prev_mouse_press: bool,
 }
 
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
 pub struct GameApiInput {
     pub mouse_pos: Vector2,
     pub mouse_press: bool,
+    /// spell slot to select, starting on 0 like SpellDto.slot, negative keep the current
+    /// selection
+    pub select_slot: i32,
     pub request_upgrade: String,
     pub input: Vector2,
     pub delta_time: f32,
@@ -36,8 +48,19 @@ impl GameApiInput {
             "mana" => Some(PlayerUpgradeRequest::Mana),
             "casting" => Some(PlayerUpgradeRequest::SkillCasting),
             "recharge" => Some(PlayerUpgradeRequest::Recharge),
-            "firebold" => Some(PlayerUpgradeRequest::Firebold),
-            _ => None
+            // spell upgrades are encoded as "spell:<code>"
+            other => other
+                .strip_prefix("spell:")
+                .filter(|code| !code.is_empty())
+                .map(|code| PlayerUpgradeRequest::Spell(SpellCode::from(code))),
+        }
+    }
+
+    fn parse_select_slot(&self) -> Option<SpellSlot> {
+        if self.select_slot >= 0 {
+            Some(self.select_slot as SpellSlot)
+        } else {
+            None
         }
     }
 }
@@ -50,12 +73,35 @@ pub struct CasterDto {
     pub calm_down: f32,
 }
 
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct SpellDto {
+    pub slot: i32,
+    pub code: String,
+    pub level: i32,
+    pub max_level: i32,
+    pub mana_cost: f32,
+    /// caster is idle and has enough mana
+    pub can_cast: bool,
+}
+
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
 pub struct CritterDto {
     pub hp: f32,
     pub max_hp: f32,
 }
 
+/// hp bar of a boss
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct BossDto {
+    pub id: Id,
+    pub model: String,
+    pub hp: f32,
+    pub max_hp: f32,
+    /// current phase starting on 0, -1 before the first phase
+    pub phase: i32,
+    pub phases: i32,
+}
+
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
 pub struct ObjDto {
     pub id: Id,
@@ -63,6 +109,23 @@ pub struct ObjDto {
     pub angle: f32,
     pub current_speed: f32,
     pub model: String,
+    /// radius of persistent areas, zero for other objects
+    pub radius: f32,
+}
+
+/// static obstacle, sizes are already multiplied by the collider scale
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct ObstacleDto {
+    pub id: Id,
+    pub model: String,
+    pub pos: Vector2,
+    pub angle: f32,
+    /// circle, aabb, capsule or rect
+    pub shape: String,
+    /// circle and capsule radius
+    pub radius: f32,
+    /// aabb and rect half extents, capsule half length on x
+    pub half_extents: Vector2,
 }
 
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
@@ -70,7 +133,12 @@ pub struct ObjChangeDto {
     pub id: Id,
     pub pos: Vector2,
     pub angle: f32,
+    /// position on the previous tick, to interpolate with GameApiOutput.alpha
+    pub prev_pos: Vector2,
+    pub prev_angle: f32,
     pub current_speed: f32,
+    /// codes of the active status effects, like burn or stun
+    pub statuses: Vec<String>,
 }
 
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
@@ -82,6 +150,42 @@ pub struct PlayerDto {
     pub score_next_level: i32,
     pub level: i32,
     pub free_skill_points: i32,
+    pub selected_slot: i32,
+    pub spells: Vec<SpellDto>,
+}
+
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct ExplosionDto {
+    pub pos: Vector2,
+    pub radius: f32,
+}
+
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct GameStateDto {
+    /// running, paused or player_dead
+    pub state: String,
+    /// final outcome, only filled when the player is dead
+    pub score: i32,
+    pub level: i32,
+    pub time_survived: f32,
+}
+
+/// flat representation of a domain GameEvent, unused fields are left as default
+#[derive(ToVariant, FromVariant, Debug, Clone, Default)]
+pub struct GameEventDto {
+    /// damaged, killed, cast_started, spell_cast, level_up, mana_depleted, projectile_expired,
+    /// player_died, wave_started, boss_wave_started, wave_cleared, boss_phase_changed,
+    /// boss_defeated
+    pub kind: String,
+    /// entity the event is about
+    pub id: Id,
+    /// entity that caused the event, only valid when has_source is set
+    pub source: Id,
+    pub has_source: bool,
+    pub amount: f32,
+    /// score, level, wave or boss phase
+    pub value: i32,
+    pub spell: String,
 }
 
 #[derive(ToVariant, FromVariant, Debug, Clone, Default)]
@@ -89,7 +193,18 @@ pub struct GameApiOutput {
     pub player: PlayerDto,
     pub objects: Vec<ObjChangeDto>,
     pub added: Vec<ObjDto>,
+    /// obstacles are reported apart from the added objects, they carry their shape
+    pub added_obstacles: Vec<ObstacleDto>,
     pub removed: Vec<Id>,
+    pub explosions: Vec<ExplosionDto>,
+    /// codes of requests that failed on this frame, like "not_enough_mana"
+    pub feedback: Vec<String>,
+    pub events: Vec<GameEventDto>,
+    /// alive bosses, to draw their hp bars
+    pub bosses: Vec<BossDto>,
+    pub game_state: GameStateDto,
+    /// interpolation between prev_pos and pos, always 1.0 without fixed timestep
+    pub alpha: f32,
 }
 
 #[methods]
@@ -115,29 +230,135 @@ impl GameApi {
             .expect("fail to start scenery");
     }
 
+    /// start the scenery with a cfg file, `.ron` or `.json`, the path must be a global file path.
+    /// An invalid file is logged and the default cfg is used instead, returns false in that case
     #[method]
-    pub fn new_run_update_input(&self) -> GameApiInput {
-        GameApiInput::default()
+    pub fn start_scenery_with_cfg(&mut self, screen_size: Vector2, cfg_path: String) -> bool {
+        let (cfg, loaded) = match Cfg::load(Path::new(&cfg_path)) {
+            Ok(cfg) => (cfg, true),
+            Err(e) => {
+                log::error!("fail to load cfg {}, using the default: {:?}", cfg_path, e);
+                (Cfg::default(), false)
+            }
+        };
+        self.api
+            .start_scenery(SceneryParams {
+                screen_size: g2v(screen_size),
+                seed: 0,
+                cfg,
+            })
+            .expect("fail to start scenery");
+        loaded
     }
 
+    /// start the current scenery again, all existing objects are reported as removed on the next
+    /// update
     #[method]
-    pub fn run_update(&mut self, input: GameApiInput) -> GameApiOutput {
-        let spell = if input.mouse_press {
-            Some(self.api.get_scenery_params().cfg.spells[0].spell_code.clone())
+    pub fn restart(&mut self) {
+        self.api.restart().expect("fail to restart");
+    }
+
+    #[method]
+    pub fn pause(&mut self) {
+        self.api.pause();
+    }
+
+    #[method]
+    pub fn resume(&mut self) {
+        self.api.resume();
+    }
+
+    #[method]
+    pub fn save_snapshot(&mut self, snapshot_path: String) {
+        self.api
+            .save_snapshot()
+            .and_then(|snapshot| snapshot.save(Path::new(&snapshot_path)))
+            .expect("fail to save snapshot");
+    }
+
+    /// replace the current scenery, removed and added objects are reported on the next update
+    #[method]
+    pub fn load_snapshot(&mut self, snapshot_path: String) {
+        let snapshot = Snapshot::load(Path::new(&snapshot_path)).expect("fail to load snapshot");
+        self.api
+            .load_snapshot(&snapshot)
+            .expect("fail to load snapshot");
+    }
+
+    #[method]
+    pub fn start_recording(&mut self) {
+        self.api.start_recording().expect("fail to start recording");
+    }
+
+    /// stop the recording and save the replay into the given path
+    #[method]
+    pub fn save_recording(&mut self, replay_path: String) {
+        match self.api.stop_recording() {
+            Some(replay) => replay
+                .save(Path::new(&replay_path))
+                .expect("fail to save replay"),
+            None => log::warn!("no recording to save"),
+        }
+    }
+
+    /// start the replay scenery and run all its frames
+    #[method]
+    pub fn play_replay(&mut self, replay_path: String) {
+        let replay = Replay::load(Path::new(&replay_path)).expect("fail to load replay");
+        self.api.play_replay(&replay).expect("fail to play replay");
+    }
+
+    /// run the simulation in fixed ticks of delta_time seconds, 0 to disable
+    #[method]
+    pub fn set_fixed_timestep(&mut self, delta_time: f32) {
+        if delta_time > 0.0 {
+            self.api.set_fixed_timestep(Some(DeltaTime(delta_time)));
         } else {
-            None
-        };
+            self.api.set_fixed_timestep(None);
+        }
+    }
+
+    #[method]
+    pub fn step(&mut self, ticks: i64) {
+        self.api.step(ticks.max(0) as u32).expect("fail to step");
+    }
 
+    /// all obstacles of the scenery, the same are reported on added_obstacles when the
+    /// scenery starts
+    #[method]
+    pub fn list_obstacles(&self) -> Vec<ObstacleDto> {
+        let entities = self.api.world.entities();
+        let obstacle_repo = self.api.world.read_storage::<Obstacle>();
+        (&entities, &obstacle_repo)
+            .join()
+            .filter_map(|(e, _)| self.get_obstacle(e).ok())
+            .collect()
+    }
+
+    #[method]
+    pub fn new_run_update_input(&self) -> GameApiInput {
+        GameApiInput {
+            select_slot: -1,
+            ..Default::default()
+        }
+    }
+
+    #[method]
+    pub fn run_update(&mut self, input: GameApiInput) -> GameApiOutput {
         let player_input = PlayerInput {
             input_dir: g2v(input.input),
             mouse_pos: g2v(input.mouse_pos),
-            cast: spell,
+            cast: None,
+            select_slot: input.parse_select_slot(),
+            cast_selected: input.mouse_press && !self.prev_mouse_press,
             upgrade: input.parse_request_upgrade(),
         };
 
+        self.prev_mouse_press = input.mouse_press;
         self.set_player_input(player_input).expect("fail to set player input");
 
         let mut added = vec![];
+        let mut added_obstacles = vec![];
         let mut removed = vec![];
 
         self.api
@@ -150,13 +371,27 @@ impl GameApi {
         }
 
         for id in events.added {
-            if let Ok(data) = self.get_object(id) {
+            if let Ok(data) = self.get_obstacle(id) {
+                added_obstacles.push(data);
+            } else if let Ok(data) = self.get_object(id) {
                 added.push(data);
             } else {
                 log::warn!("could not find obj id {:?}", id);
             }
         }
 
+        let explosions = events
+            .explosions
+            .into_iter()
+            .map(|e| ExplosionDto {
+                pos: v2g(e.pos),
+                radius: e.radius,
+            })
+            .collect();
+
+        let feedback = events.feedback.iter().map(feedback_code).collect();
+        let game_events = events.game_events.iter().map(game_event_dto).collect();
+
         let player_dto = self.get_player_data().expect("fail get player data");
 
         let objects_dto = self
@@ -169,7 +404,14 @@ impl GameApi {
             player: player_dto,
             objects: objects_dto,
             added: added,
+            added_obstacles,
             removed: removed,
+            explosions,
+            feedback,
+            events: game_events,
+            bosses: self.list_bosses(),
+            game_state: self.get_game_state(),
+            alpha: self.api.get_interpolation_alpha(),
         }
     }
 }
@@ -183,10 +425,14 @@ impl GameApi {
         let caster_repo = self.api.world.read_storage::<Caster>();
         let entities = self.api.world.entities();
         let damagables = self.api.world.read_storage::<Damageable>();
+        let prev_position_repo = self.api.world.read_storage::<PrevPosition>();
+        let status_repo = self.api.world.read_storage::<StatusEffects>();
 
-        let (e, pos, pla, _cri, vel, cas, dam) = (
+        let (e, pos, prev, st, pla, _cri, vel, cas, dam) = (
             &entities,
             &position_repo,
+            prev_position_repo.maybe(),
+            status_repo.maybe(),
             &player_repo,
             &critter_repo,
             &velocities_repo,
@@ -204,12 +450,33 @@ impl GameApi {
             calm_down: cas.casting.get_calm_down().unwrap_or(0.0),
         };
 
+        let spells = cas
+            .spell_book
+            .spells
+            .iter()
+            .enumerate()
+            .map(|(slot, entry)| {
+                let current = entry.current();
+                SpellDto {
+                    slot: slot as i32,
+                    code: entry.spell.spell_code.to_string(),
+                    level: entry.level,
+                    max_level: entry.max_level(),
+                    mana_cost: current.map(|s| s.mana_cost).unwrap_or(0.0),
+                    can_cast: current.map(|s| cas.can_cast(s)).unwrap_or(false),
+                }
+            })
+            .collect();
+
         Ok(PlayerDto {
             obj: ObjChangeDto {
                 id: encode_entity(e),
                 pos: v2g(pos.pos),
                 angle: pos.angle,
+                prev_pos: v2g(prev.map(|p| p.pos).unwrap_or(pos.pos)),
+                prev_angle: prev.map(|p| p.angle).unwrap_or(pos.angle),
                 current_speed: vel.vel.length(),
+                statuses: status_codes(st),
             },
             critter: CritterDto {
                 hp: dam.hp,
@@ -220,30 +487,72 @@ impl GameApi {
             score_next_level: pla.next_level_required_score(),
             level: pla.level(),
             free_skill_points: pla.free_skill_points(),
+            selected_slot: pla.selected_slot() as i32,
+            spells,
         })
     }
 
+    pub fn get_game_state(&self) -> GameStateDto {
+        match self.api.get_game_state() {
+            GameState::Running => GameStateDto {
+                state: "running".to_string(),
+                ..Default::default()
+            },
+            GameState::Paused => GameStateDto {
+                state: "paused".to_string(),
+                ..Default::default()
+            },
+            GameState::PlayerDead(outcome) => GameStateDto {
+                state: "player_dead".to_string(),
+                score: outcome.score,
+                level: outcome.level,
+                time_survived: outcome.time_survived.as_seconds_f64() as f32,
+            },
+        }
+    }
+
     pub fn set_player_input(&mut self, input: PlayerInput) -> Result<(), GameError> {
         self.api.set_player_input(input)
     }
 
+    pub fn list_bosses(&self) -> Vec<BossDto> {
+        let entities = self.api.world.entities();
+        let boss_repo = self.api.world.read_storage::<Boss>();
+        let damageable_repo = self.api.world.read_storage::<Damageable>();
+        let model_repo = self.api.world.read_storage::<HasModel>();
+
+        (&entities, &boss_repo, &damageable_repo, &model_repo)
+            .join()
+            .map(|(e, boss, dam, model)| BossDto {
+                id: encode_entity(e),
+                model: model.model.to_string(),
+                hp: dam.hp.max(0.0),
+                max_hp: dam.max_hp,
+                phase: boss.phase.map(|phase| phase as i32).unwrap_or(-1),
+                phases: boss.cfg.phases.len() as i32,
+            })
+            .collect()
+    }
+
     pub fn get_object(&self, id: Entity) -> Result<ObjDto, GameError> {
         let position_repo = self.api.world.read_storage::<Position>();
         let player_repo = self.api.world.read_storage::<Player>();
         let critter_repo = self.api.world.read_storage::<Critter>();
         let velocities_repo = self.api.world.read_storage::<Velocity>();
         let model_repo = self.api.world.read_storage::<HasModel>();
+        let area_repo = self.api.world.read_storage::<Area>();
 
         let mut bs = BitSet::new();
         bs.add(id.id());
 
-        for (_, pos, _, _cri, vel, model) in (
+        for (_, pos, _, _cri, vel, model, area) in (
             &bs,
             &position_repo,
             !&player_repo,
             critter_repo.maybe(),
-            &velocities_repo,
+            velocities_repo.maybe(),
             &model_repo,
+            area_repo.maybe(),
         )
             .join()
         {
@@ -251,29 +560,80 @@ impl GameApi {
                 id: encode_entity(id),
                 pos: v2g(pos.pos),
                 angle: pos.angle,
-                current_speed: vel.vel.length(),
+                current_speed: vel.map(|v| v.vel.length()).unwrap_or(0.0),
                 model: model.model.to_string(),
+                radius: area.map(|a| a.radius).unwrap_or(0.0),
             });
         }
 
         Err(GameError::Str("entity not found"))
     }
 
+    pub fn get_obstacle(&self, id: Entity) -> Result<ObstacleDto, GameError> {
+        let obstacle_repo = self.api.world.read_storage::<Obstacle>();
+        let position_repo = self.api.world.read_storage::<Position>();
+        let collider_repo = self.api.world.read_storage::<Collider>();
+        let model_repo = self.api.world.read_storage::<HasModel>();
+
+        if !obstacle_repo.contains(id) {
+            return Err(GameError::Str("entity is not an obstacle"));
+        }
+
+        let pos = position_repo
+            .get(id)
+            .ok_or(GameError::Str("entity not found"))?;
+        let col = collider_repo
+            .get(id)
+            .ok_or(GameError::Str("obstacle has no collider"))?;
+        let model = model_repo
+            .get(id)
+            .map(|m| m.model.to_string())
+            .unwrap_or_default();
+
+        let (shape, radius, half_extents) = match &col.shape {
+            Shape::Circle => ("circle", col.scale, Vector2::ZERO),
+            Shape::Aabb { half_extents } => ("aabb", 0.0, v2g(*half_extents * col.scale)),
+            Shape::Rect { half_extents } => ("rect", 0.0, v2g(*half_extents * col.scale)),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => (
+                "capsule",
+                radius * col.scale,
+                Vector2::new(half_length * col.scale, 0.0),
+            ),
+        };
+
+        Ok(ObstacleDto {
+            id: encode_entity(id),
+            model,
+            pos: v2g(pos.pos),
+            angle: pos.angle,
+            shape: shape.to_string(),
+            radius,
+            half_extents,
+        })
+    }
+
     pub fn list_objects(&self) -> Result<Vec<ObjChangeDto>, GameError> {
         let position_repo = self.api.world.read_storage::<Position>();
         let player_repo = self.api.world.read_storage::<Player>();
         let critter_repo = self.api.world.read_storage::<Critter>();
         let velocities_repo = self.api.world.read_storage::<Velocity>();
+        let prev_position_repo = self.api.world.read_storage::<PrevPosition>();
+        let status_repo = self.api.world.read_storage::<StatusEffects>();
         let entities = self.api.world.entities();
 
         let mut result = vec![];
 
-        for (e, pos, _, _cri, vel) in (
+        for (e, pos, prev, st, _, _cri, vel) in (
             &entities,
             &position_repo,
+            prev_position_repo.maybe(),
+            status_repo.maybe(),
             !&player_repo,
             critter_repo.maybe(),
-            &velocities_repo,
+            velocities_repo.maybe(),
         )
             .join()
         {
@@ -281,7 +641,10 @@ impl GameApi {
                 id: encode_entity(e),
                 pos: v2g(pos.pos),
                 angle: pos.angle,
-                current_speed: vel.vel.length(),
+                prev_pos: v2g(prev.map(|p| p.pos).unwrap_or(pos.pos)),
+                prev_angle: prev.map(|p| p.angle).unwrap_or(pos.angle),
+                current_speed: vel.map(|v| v.vel.length()).unwrap_or(0.0),
+                statuses: status_codes(st),
             })
         }
 
@@ -289,6 +652,141 @@ impl GameApi {
     }
 }
 
+fn status_codes(statuses: Option<&StatusEffects>) -> Vec<String> {
+    statuses
+        .map(|st| {
+            st.effects
+                .iter()
+                .map(|active| {
+                    let code = match active.cfg.kind {
+                        StatusKind::Burn => "burn",
+                        StatusKind::Slow => "slow",
+                        StatusKind::Freeze => "freeze",
+                        StatusKind::Poison => "poison",
+                        StatusKind::Stun => "stun",
+                    };
+                    code.to_string()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn feedback_code(feedback: &Feedback) -> String {
+    let code = match feedback {
+        Feedback::CastFailed(CastError::NotEnoughMana) => "not_enough_mana",
+        Feedback::CastFailed(CastError::Busy) => "busy",
+        Feedback::CastFailed(CastError::CalmDown) => "calm_down",
+        Feedback::CastFailed(CastError::UnknownSpell(_)) => "unknown_spell",
+        Feedback::CastFailed(CastError::SpellLevelMissing(_)) => "spell_level_missing",
+        Feedback::CastFailed(CastError::Stunned) => "stunned",
+        Feedback::UpgradeFailed(UpgradeError::NoSkillPoints) => "no_skill_points",
+        Feedback::UpgradeFailed(UpgradeError::UnknownSpell(_)) => "unknown_spell",
+        Feedback::UpgradeFailed(UpgradeError::SpellMaxLevel(_)) => "spell_max_level",
+    };
+    code.to_string()
+}
+
+fn game_event_dto(event: &GameEvent) -> GameEventDto {
+    match event {
+        GameEvent::Damaged {
+            target,
+            source,
+            amount,
+        } => GameEventDto {
+            kind: "damaged".to_string(),
+            id: encode_entity(*target),
+            source: encode_entity(*source),
+            has_source: true,
+            amount: *amount,
+            ..Default::default()
+        },
+        GameEvent::Killed {
+            entity,
+            killer,
+            score,
+        } => GameEventDto {
+            kind: "killed".to_string(),
+            id: encode_entity(*entity),
+            source: encode_entity(*killer),
+            has_source: true,
+            value: *score,
+            ..Default::default()
+        },
+        GameEvent::CastStarted {
+            caster,
+            spell,
+            mana,
+        } => GameEventDto {
+            kind: "cast_started".to_string(),
+            id: encode_entity(*caster),
+            amount: *mana,
+            spell: spell.to_string(),
+            ..Default::default()
+        },
+        GameEvent::SpellCast { caster, spell } => GameEventDto {
+            kind: "spell_cast".to_string(),
+            id: encode_entity(*caster),
+            spell: spell.to_string(),
+            ..Default::default()
+        },
+        GameEvent::LevelUp { player, level } => GameEventDto {
+            kind: "level_up".to_string(),
+            id: encode_entity(*player),
+            value: *level,
+            ..Default::default()
+        },
+        GameEvent::ManaDepleted { caster } => GameEventDto {
+            kind: "mana_depleted".to_string(),
+            id: encode_entity(*caster),
+            ..Default::default()
+        },
+        GameEvent::ProjectileExpired { entity } => GameEventDto {
+            kind: "projectile_expired".to_string(),
+            id: encode_entity(*entity),
+            ..Default::default()
+        },
+        GameEvent::PlayerDied { player } => GameEventDto {
+            kind: "player_died".to_string(),
+            id: encode_entity(*player),
+            ..Default::default()
+        },
+        GameEvent::WaveStarted { wave, boss } => GameEventDto {
+            kind: if *boss {
+                "boss_wave_started"
+            } else {
+                "wave_started"
+            }
+            .to_string(),
+            value: *wave as i32,
+            ..Default::default()
+        },
+        GameEvent::WaveCleared { wave } => GameEventDto {
+            kind: "wave_cleared".to_string(),
+            value: *wave as i32,
+            ..Default::default()
+        },
+        GameEvent::BossPhaseChanged { boss, phase } => GameEventDto {
+            kind: "boss_phase_changed".to_string(),
+            id: encode_entity(*boss),
+            value: *phase as i32,
+            ..Default::default()
+        },
+        GameEvent::BossDefeated {
+            boss,
+            killer,
+            score,
+        } => GameEventDto {
+            kind: "boss_defeated".to_string(),
+            id: encode_entity(*boss),
+            source: encode_entity(*killer),
+            has_source: true,
+            value: *score,
+            ..Default::default()
+        },
+    }
+}
+
 // Function that registers all exposed classes to Godot
 fn init(handle: InitHandle) {
     handle.add_class::<GameApi>();